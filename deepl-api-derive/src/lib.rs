@@ -0,0 +1,61 @@
+//! Provides `#[derive(Translate)]` for the [deepl-api](https://docs.rs/deepl-api) crate.
+//!
+//! Don't use this crate directly, but enable the `derive` feature of `deepl-api` instead, which
+//! re-exports the macro as `deepl_api::Translate`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Index};
+
+/// Implements `deepl_api::Translate` for a struct. All fields annotated with `#[translate]` are
+/// translated by `DeepL::translate_struct`. Supported field types are `String`, `Option<String>`
+/// and `Vec<String>`.
+#[proc_macro_derive(Translate, attributes(translate))]
+pub fn derive_translate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "#[derive(Translate)] is only supported for structs",
+            ))
+        }
+    };
+
+    let mut accessors = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attribute = match field.attrs.iter().find(|a| a.path().is_ident("translate")) {
+            Some(attribute) => attribute,
+            None => continue,
+        };
+        attribute.meta.require_path_only()?;
+        accessors.push(match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = Index::from(i);
+                quote!(#index)
+            }
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::deepl_api::Translate for #name #ty_generics #where_clause {
+            fn translatable_fields(&mut self) -> ::std::vec::Vec<&mut ::std::string::String> {
+                let mut fields = ::std::vec::Vec::new();
+                #(::deepl_api::TranslatableField::collect_fields(&mut self.#accessors, &mut fields);)*
+                fields
+            }
+        }
+    })
+}
@@ -1,24 +1,58 @@
-pub use clap::Clap;
+pub use clap::Parser;
+use clap::{Args, Subcommand, ValueEnum};
 
 /// Command line client for the DeepL API.
-#[derive(Clap)]
-#[clap(name = "deepl",version(env!("CARGO_PKG_VERSION")),setting(clap::AppSettings::GlobalVersion))]
+#[derive(Parser)]
+#[clap(name = "deepl", version, propagate_version = true)]
 pub struct Opts {
+    /// Format of error output on STDERR
+    #[clap(long, global = true, value_enum, default_value = "text")]
+    pub errors: ErrorFormat,
+
+    /// Format of results on STDOUT
+    #[clap(long, global = true, value_enum, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Abort before the characters sent for translation in this invocation would exceed this number
+    #[clap(long, global = true)]
+    pub max_chars: Option<u64>,
+
     #[clap(subcommand)]
     pub subcmd: SubCommand,
 }
 
-#[derive(Clap)]
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ErrorFormat {
+    /// Human readable error messages.
+    Text,
+    /// One JSON record per failed item with file, segment, error kind and message.
+    Jsonl,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable output.
+    Text,
+    /// A versioned JSON document, see the `schema` module of the library.
+    Json,
+}
+
+#[derive(Subcommand)]
 pub enum SubCommand {
     Translate(Translate),
+    Compare(Compare),
+    Stream(Stream),
     /// Fetch imformation about account usage & limits.
     UsageInformation,
     /// Fetch list of available source and target languages.
     Languages,
+    /// Check the environment setup and connection to the DeepL API.
+    Doctor,
+    Report(Report),
 }
 
 /// A subcommand for controlling testing
-#[derive(Clap)]
+#[derive(Args)]
 pub struct Translate {
     /// Source language (optional)
     #[clap(long)]
@@ -32,6 +66,14 @@ pub struct Translate {
     /// Output filepath (optional, prints to STDOUT by default)
     #[clap(long)]
     pub output_file: Option<String>,
+    /// Output filepath template, e. g. '{dir}/{stem}.{target_lang}.{ext}' (optional, requires
+    /// --input-file). Available placeholders: {dir}, {stem}, {ext}, {target_lang}
+    #[clap(long, conflicts_with = "output-file", requires = "input-file")]
+    pub output_template: Option<String>,
+    /// Encoding of the output, e. g. 'shift_jis' (optional, UTF-8 by default). Can be given per
+    /// target language as 'JA=shift_jis' and repeated
+    #[clap(long, value_name = "[LANG=]ENCODING")]
+    pub output_encoding: Vec<String>,
 
     /// Preserve formatting
     #[clap(long)]
@@ -43,3 +85,87 @@ pub struct Translate {
     #[clap(long)]
     pub formality_less: bool,
 }
+
+/// Translate a field of JSONL records from STDIN and write them to STDOUT
+#[derive(Args)]
+pub struct Stream {
+    /// Source language (optional)
+    #[clap(long, visible_alias = "from")]
+    pub source_language: Option<String>,
+    /// Target language (required)
+    #[clap(long, visible_alias = "to")]
+    pub target_language: String,
+    /// Name of the field to translate
+    #[clap(long, default_value = "text")]
+    pub field: String,
+    /// Name of the field to write the translation to (defaults to the translated field)
+    #[clap(long)]
+    pub output_field: Option<String>,
+    /// Number of records to translate per request
+    #[clap(long, default_value = "50")]
+    pub batch_size: usize,
+}
+
+/// Report missing, extra, untranslated and outdated keys of JSON locale files compared to the
+/// source locale. Only JSON files are supported
+#[derive(Args)]
+pub struct Report {
+    /// Directory containing one <locale>.json file per locale
+    pub directory: String,
+    /// Locale of the source file
+    #[clap(long, default_value = "en")]
+    pub source_locale: String,
+    /// Fail if the completeness of any locale is below this percentage
+    #[clap(long)]
+    pub min_completeness: Option<f64>,
+    /// JSON file with the source messages from when the translations were last updated. Keys
+    /// whose source message changed since are reported as outdated. Without it, outdated keys
+    /// are not detected
+    #[clap(long)]
+    pub snapshot: Option<String>,
+    /// Write the current source messages to the --snapshot file after reporting, e. g. once all
+    /// locales are up to date. Creates the file if it does not exist yet
+    #[clap(long, requires = "snapshot")]
+    pub update_snapshot: bool,
+}
+
+/// Compare translations with different model types or formality settings
+#[derive(Args)]
+pub struct Compare {
+    /// Input filepath (optional, reads from STDIN by default). Each line is compared separately
+    pub input_file: Option<String>,
+    /// Source language (optional)
+    #[clap(long, visible_alias = "from")]
+    pub source_language: Option<String>,
+    /// Target language (required)
+    #[clap(long, visible_alias = "to")]
+    pub target_language: String,
+    /// Model types to compare
+    #[clap(
+        long,
+        value_enum,
+        use_value_delimiter = true,
+        required_unless_present = "formalities"
+    )]
+    pub model_types: Vec<CompareModelType>,
+    /// Formality settings to compare
+    #[clap(long, value_enum, use_value_delimiter = true)]
+    pub formalities: Vec<CompareFormality>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CompareModelType {
+    /// The classic model
+    #[clap(alias = "latency-optimized")]
+    Classic,
+    /// The next-gen model
+    #[clap(alias = "quality-optimized")]
+    NextGen,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CompareFormality {
+    Default,
+    More,
+    Less,
+}
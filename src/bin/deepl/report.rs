@@ -0,0 +1,169 @@
+//! Locale completeness report for `deepl report`.
+//!
+//! Only JSON locale files are supported. Outdated keys are detected against a snapshot of the
+//! source messages, which is a JSON file with the messages of the source locale at the time the
+//! translations were last brought up to date.
+
+use deepl_api::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Comparison of one target locale against the source locale.
+pub struct LocaleReport {
+    pub locale: String,
+    pub total: usize,
+    pub missing: Vec<String>,
+    pub extra: Vec<String>,
+    pub untranslated: Vec<String>,
+    /// Keys whose source message changed since the snapshot, if one was given.
+    pub outdated: Option<Vec<String>>,
+}
+
+impl LocaleReport {
+    /// Percentage of source keys that are present in the target locale.
+    pub fn completeness(&self) -> f64 {
+        match self.total {
+            0 => 100.0,
+            total => (total - self.missing.len()) as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+/// Compares all `<locale>.json` files in `dir` against the one of `source_locale`. If a
+/// `snapshot` of the source messages is given, the keys whose source message changed since are
+/// reported as outdated.
+pub fn locale_reports(
+    dir: &Path,
+    source_locale: &str,
+    snapshot: Option<&Path>,
+) -> Result<Vec<LocaleReport>> {
+    let snapshot_path = snapshot.and_then(|path| path.canonicalize().ok());
+    let snapshot = match snapshot {
+        Some(path) if !path.is_file() => {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("snapshot {} not found", path.display()),
+            )
+            .into())
+        }
+        Some(path) => Some(read_messages(path)?),
+        None => None,
+    };
+    let mut locales = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        // The snapshot may be kept next to the locale files.
+        if snapshot_path.is_some() && path.canonicalize().ok() == snapshot_path {
+            continue;
+        }
+        if let Some(locale) = path.file_stem().and_then(|s| s.to_str()) {
+            locales.insert(locale.to_string(), read_messages(&path)?);
+        }
+    }
+
+    let source = match locales.remove(source_locale) {
+        Some(source) => source,
+        None => return Err(no_source_locale(dir, source_locale)),
+    };
+
+    Ok(locales
+        .into_iter()
+        .map(|(locale, target)| LocaleReport {
+            locale,
+            total: source.len(),
+            missing: source
+                .keys()
+                .filter(|key| !target.contains_key(*key))
+                .cloned()
+                .collect(),
+            extra: target
+                .keys()
+                .filter(|key| !source.contains_key(*key))
+                .cloned()
+                .collect(),
+            untranslated: source
+                .iter()
+                .filter(|(key, value)| target.get(*key) == Some(value) && !value.is_empty())
+                .map(|(key, _)| key.clone())
+                .collect(),
+            outdated: snapshot.as_ref().map(|snapshot| {
+                source
+                    .iter()
+                    .filter(|(key, value)| {
+                        target.contains_key(*key)
+                            && snapshot.get(*key).is_some_and(|old| old != *value)
+                    })
+                    .map(|(key, _)| key.clone())
+                    .collect()
+            }),
+        })
+        .collect())
+}
+
+/// Writes the current messages of `source_locale` in `dir` to the snapshot file at `path`.
+pub fn write_snapshot(path: &Path, dir: &Path, source_locale: &str) -> Result<()> {
+    let source_path = dir.join(format!("{}.json", source_locale));
+    if !source_path.is_file() {
+        return Err(no_source_locale(dir, source_locale));
+    }
+    let messages = read_messages(&source_path)?;
+    let json = serde_json::to_string_pretty(&messages).map_err(io::Error::other)?;
+    fs::write(path, json + "\n")?;
+    Ok(())
+}
+
+fn no_source_locale(dir: &Path, source_locale: &str) -> deepl_api::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!(
+            "no file for source locale '{}' found in {}",
+            source_locale,
+            dir.display()
+        ),
+    )
+    .into()
+}
+
+/// Reads a JSON locale file into a flat map of dotted keys to messages.
+fn read_messages(path: &Path) -> Result<BTreeMap<String, String>> {
+    let content = fs::read_to_string(path)?;
+    let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid JSON in {}: {}", path.display(), e),
+        )
+    })?;
+    let mut messages = BTreeMap::new();
+    flatten("", &value, &mut messages);
+    Ok(messages)
+}
+
+fn flatten(prefix: &str, value: &serde_json::Value, messages: &mut BTreeMap<String, String>) {
+    let join = |key: &str| match prefix.is_empty() {
+        true => key.to_string(),
+        false => format!("{}.{}", prefix, key),
+    };
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                flatten(&join(key), value, messages);
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                flatten(&join(&i.to_string()), value, messages);
+            }
+        }
+        serde_json::Value::String(text) => {
+            messages.insert(prefix.to_string(), text.clone());
+        }
+        other => {
+            messages.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
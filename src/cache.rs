@@ -0,0 +1,333 @@
+//! Caching of translations, e. g. for UI localization where the same strings are translated
+//! over and over again.
+//!
+//! Attach a [TranslationCache] with
+//! [DeepL::with_translation_cache](crate::DeepL::with_translation_cache) to answer repeated
+//! translations from memory. Texts are cached individually, keyed on the text, the source and
+//! target language and the [options](crate::TranslationOptions), so a request with some known
+//! texts only sends the others. Cached translations cost nothing: if the billed characters are
+//! [requested](crate::TranslationOptions::show_billed_characters), they are reported as zero.
+//!
+//! A [persistent](TranslationCache::persistent) cache additionally keeps the translations in a
+//! [Store], so that they survive restarts of the process: re-running a localization pipeline
+//! against a [FileStore](crate::store::FileStore) or `SqliteStore` only pays for changed texts.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use deepl_api::cache::TranslationCache;
+//! use deepl_api::*;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! let cache = Arc::new(TranslationCache::new().with_ttl(Duration::from_secs(24 * 60 * 60)));
+//! let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap(), false)
+//!     .with_translation_cache(cache.clone());
+//!
+//! for _ in 0..2 {
+//!     let texts = TranslatableTextList::new(Language::De, vec!["Save", "Cancel"]);
+//!     deepl.translate(None, texts).unwrap();
+//! }
+//! assert_eq!(cache.statistics().hits, 2);
+//! assert_eq!(cache.statistics().misses, 2);
+//! ```
+
+use crate::store::Store;
+use crate::{TranslatedText, TranslationOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const NAMESPACE: &str = "cache";
+
+/// Hit and miss counts of a [TranslationCache].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CacheStatistics {
+    /// Number of texts answered from the cache.
+    pub hits: u64,
+    /// Number of texts that had to be translated.
+    pub misses: u64,
+    /// Number of translations cached in memory, including expired ones that were not looked up
+    /// since. Persisted translations are only counted once they were loaded.
+    pub entries: usize,
+}
+
+impl CacheStatistics {
+    /// The share of texts answered from the cache, between 0 and 1. Zero if nothing was
+    /// looked up yet.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
+
+/// One translation as persisted in the [Store]. The store key is a hash, so the full cache key
+/// is kept to detect collisions.
+#[derive(Debug, Deserialize, Serialize)]
+struct StoredTranslation {
+    key: String,
+    translation: TranslatedText,
+    /// Seconds since the unix epoch.
+    cached_at: u64,
+}
+
+/// A cache of translations, see the [module documentation](self).
+#[derive(Default)]
+pub struct TranslationCache {
+    ttl: Option<Duration>,
+    store: Option<Arc<dyn Store>>,
+    entries: Mutex<HashMap<String, (TranslatedText, Instant)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl fmt::Debug for TranslationCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TranslationCache")
+            .field("ttl", &self.ttl)
+            .field("persistent", &self.store.is_some())
+            .field("statistics", &self.statistics())
+            .finish()
+    }
+}
+
+impl TranslationCache {
+    /// Create an empty in-memory cache whose entries never expire.
+    pub fn new() -> TranslationCache {
+        TranslationCache::default()
+    }
+
+    /// Create a cache that also keeps its translations in the namespace `cache` of `store`,
+    /// and loads them from there on demand. Failures of the store are logged and treated like
+    /// a miss, so they never fail a translation.
+    pub fn persistent(store: Arc<dyn Store>) -> TranslationCache {
+        TranslationCache {
+            store: Some(store),
+            ..TranslationCache::default()
+        }
+    }
+
+    /// Let entries expire `ttl` after they were cached, e. g. to pick up improvements of the
+    /// translation models.
+    pub fn with_ttl(mut self, ttl: Duration) -> TranslationCache {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// The hit and miss counts so far.
+    pub fn statistics(&self) -> CacheStatistics {
+        CacheStatistics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.lock().len(),
+        }
+    }
+
+    /// Remove all cached translations, including the persisted ones. The statistics are kept.
+    pub fn clear(&self) -> crate::Result<()> {
+        self.lock().clear();
+        if let Some(store) = &self.store {
+            for (key, _) in store.scan(NAMESPACE, "")? {
+                store.delete(NAMESPACE, &key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up the translation of one text, counting a hit or a miss.
+    pub(crate) fn get(&self, key: &str) -> Option<TranslatedText> {
+        let mut entries = self.lock();
+        let translation = match entries.get(key) {
+            Some((_, cached)) if self.is_expired(cached.elapsed()) => {
+                entries.remove(key);
+                None
+            }
+            Some((translation, _)) => Some(translation.clone()),
+            None => self.load(key).map(|(translation, cached)| {
+                entries.insert(key.to_string(), (translation.clone(), cached));
+                translation
+            }),
+        };
+        let counter = match translation {
+            Some(_) => &self.hits,
+            None => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        translation
+    }
+
+    /// Cache the translation of one text.
+    pub(crate) fn insert(&self, key: String, translation: TranslatedText) {
+        if let Some(store) = &self.store {
+            let stored = StoredTranslation {
+                key: key.clone(),
+                translation: translation.clone(),
+                cached_at: unix_seconds(SystemTime::now()),
+            };
+            // Only plain data, which always serializes.
+            let value = serde_json::to_vec(&stored).expect("translations are serializable");
+            if let Err(e) = store.put(NAMESPACE, &store_key(&key), &value) {
+                log::warn!("Could not persist translation in cache: {}", e);
+            }
+        }
+        self.lock().insert(key, (translation, Instant::now()));
+    }
+
+    /// Load a translation from the store, if it is persistent and the translation has not expired.
+    fn load(&self, key: &str) -> Option<(TranslatedText, Instant)> {
+        let store = self.store.as_ref()?;
+        let value = match store.get(NAMESPACE, &store_key(key)) {
+            Ok(value) => value?,
+            Err(e) => {
+                log::warn!("Could not load translation from cache: {}", e);
+                return None;
+            }
+        };
+        let stored: StoredTranslation = serde_json::from_slice(&value).ok()?;
+        let age =
+            Duration::from_secs(unix_seconds(SystemTime::now()).saturating_sub(stored.cached_at));
+        if stored.key != key || self.is_expired(age) {
+            return None;
+        }
+        let cached = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+        Some((stored.translation, cached))
+    }
+
+    fn is_expired(&self, age: Duration) -> bool {
+        self.ttl.is_some_and(|ttl| age >= ttl)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, (TranslatedText, Instant)>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The cache key of a text. The billed characters don't change the translation, so they are
+/// not part of the key, and no options are the same as the default options.
+pub(crate) fn cache_key(
+    text: &str,
+    source_language: Option<&str>,
+    target_language: &str,
+    options: Option<&TranslationOptions>,
+) -> String {
+    let options = TranslationOptions {
+        show_billed_characters: None,
+        ..options.cloned().unwrap_or_default()
+    };
+    json!([target_language, source_language, options, text]).to_string()
+}
+
+/// The key of a translation in the [Store]: a hash of the cache key, which keeps the keys short
+/// enough for file names. FNV-1a is stable across Rust versions, unlike the std hashers.
+fn store_key(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use crate::testing::*;
+    use crate::{Formality, Language, TranslatableTextList};
+    use std::sync::Arc;
+
+    #[test]
+    fn translation_cache() {
+        let server = MockServer::deepl();
+        let cache = Arc::new(TranslationCache::new());
+        let deepl = server.client().with_translation_cache(cache.clone());
+
+        let texts = TranslatableTextList::new(Language::De, vec!["Save", "Cancel"]);
+        deepl.translate(None, texts).unwrap();
+        let options = TranslationOptions::new().show_billed_characters(true);
+        let texts = TranslatableTextList::new(Language::De, vec!["Open", "Save", "Cancel"]);
+        let translated = deepl.translate(Some(options), texts).unwrap();
+        assert_eq!(translated[0].billed_characters, Some(4));
+        assert_eq!(translated[1].billed_characters, Some(0));
+        assert_eq!(translated[2].text, "Cancel");
+
+        // Only the unknown text was sent.
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].param_values("text"), vec!["Open"]);
+
+        // Other languages, source languages and options are cached separately.
+        let options = TranslationOptions::new().formality(Formality::More);
+        let texts = TranslatableTextList::new(Language::De, vec!["Save"]);
+        deepl.translate(Some(options), texts).unwrap();
+        let texts = TranslatableTextList::new(Language::Fr, vec!["Save"]);
+        deepl.translate(None, texts).unwrap();
+        let texts = TranslatableTextList::new(Language::De, vec!["Save"])
+            .with_source_language(Language::En);
+        deepl.translate(None, texts).unwrap();
+        assert_eq!(server.requests().len(), 5);
+
+        let statistics = cache.statistics();
+        assert_eq!((statistics.hits, statistics.misses), (2, 6));
+        assert_eq!(statistics.entries, 6);
+        assert_eq!(statistics.hit_rate(), 0.25);
+        cache.clear().unwrap();
+        assert_eq!(cache.statistics().entries, 0);
+    }
+
+    #[test]
+    fn persistent_cache() {
+        let server = MockServer::deepl();
+        let store: Arc<dyn Store> = Arc::new(MemoryStore::new());
+        let texts = || TranslatableTextList::new(Language::De, vec!["Save", "Cancel"]);
+
+        let cache = Arc::new(TranslationCache::persistent(store.clone()));
+        let deepl = server.client().with_translation_cache(cache);
+        deepl.translate(None, texts()).unwrap();
+        assert_eq!(store.scan(NAMESPACE, "").unwrap().len(), 2);
+
+        // A new cache, e. g. after a restart, loads the translations from the store.
+        let cache = Arc::new(TranslationCache::persistent(store.clone()));
+        let deepl = server.client().with_translation_cache(cache.clone());
+        let translated = deepl.translate(None, texts()).unwrap();
+        assert_eq!(translated[1].text, "Cancel");
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(cache.statistics().hits, 2);
+
+        // Expired translations are not loaded.
+        let cache = TranslationCache::persistent(store.clone()).with_ttl(Duration::ZERO);
+        assert!(cache.get(&cache_key("Save", None, "DE", None)).is_none());
+
+        cache.clear().unwrap();
+        assert!(store.scan(NAMESPACE, "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn cache_expiry() {
+        let cache = TranslationCache::new().with_ttl(Duration::from_millis(20));
+        let translation = TranslatedText {
+            detected_source_language: "EN".to_string(),
+            text: "Hallo".to_string(),
+            billed_characters: None,
+            model_type_used: None,
+        };
+        let key = cache_key("Hello", None, "DE", None);
+        cache.insert(key.clone(), translation);
+        assert!(cache.get(&key).is_some());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get(&key).is_none());
+        assert_eq!(cache.statistics().entries, 0);
+    }
+}
@@ -0,0 +1,67 @@
+//! Cooperative cancellation of requests, see [DeepL::with_cancellation](crate::DeepL::with_cancellation).
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// A flag shared between a client and the code that wants to stop it, e. g. on shutdown.
+///
+/// Clones share the same flag. Once [cancelled](CancellationToken::cancel), the token stays
+/// cancelled.
+///
+/// ```rust
+/// use deepl_api::cancel::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let shutdown = token.clone();
+/// assert!(!token.is_cancelled());
+/// shutdown.cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancel all requests of the clients using this token, including waits between retries.
+    pub fn cancel(&self) {
+        let (cancelled, condvar) = &*self.inner;
+        *cancelled
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = true;
+        condvar.notify_all();
+    }
+
+    /// Whether [cancel](CancellationToken::cancel) was called.
+    pub fn is_cancelled(&self) -> bool {
+        *self
+            .inner
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Sleep for `duration`, or until the token is cancelled.
+    pub(crate) fn sleep(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        let (cancelled, condvar) = &*self.inner;
+        let mut cancelled = cancelled
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while !*cancelled {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            cancelled = condvar
+                .wait_timeout(cancelled, deadline - now)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+    }
+}
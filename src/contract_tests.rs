@@ -0,0 +1,157 @@
+//! Contract tests against the recorded requests and responses in `tests/fixtures/contract.json`.
+//!
+//! Each case describes an API call, the exact request the client must send for it, the response
+//! of the server and the expected result. Changes of the wire format, e. g. when moving parameters
+//! from the query string to the body, have to update the fixtures deliberately.
+
+use super::testing::*;
+use super::*;
+use serde_json::{json, Value};
+
+const FIXTURES: &str = include_str!("../tests/fixtures/contract.json");
+
+#[test]
+fn contract() {
+    let cases: Vec<Value> = serde_json::from_str(FIXTURES).unwrap();
+    assert!(!cases.is_empty());
+    for case in &cases {
+        check_case(case);
+    }
+}
+
+fn check_case(case: &Value) {
+    let name = case["name"].as_str().unwrap();
+    let response = case["response"].clone();
+    let server = MockServer::start(move |_| {
+        let body = match &response["body"] {
+            Value::Object(map) if map.is_empty() => String::new(),
+            body => body.to_string(),
+        };
+        MockResponse::json(response["status"].as_u64().unwrap() as u16, &body)
+    });
+
+    let call = &case["call"];
+    let mut deepl = DeepL::new("key".to_string(), false).with_base_url(server.url());
+    if call["auth_method"] == "query_parameter" {
+        deepl = deepl.with_auth_method(AuthMethod::QueryParameter);
+    }
+    let result = match call["endpoint"].as_str().unwrap() {
+        // Optional fields are only part of the expected result if present.
+        "usage" => deepl
+            .usage_information()
+            .map(|usage| serde_json::to_value(usage).unwrap()),
+        "source_languages" => deepl.source_languages().map(languages_to_json),
+        "target_languages" => deepl.target_languages().map(languages_to_json),
+        "translate" => {
+            let texts: Vec<&str> = call["texts"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|text| text.as_str().unwrap())
+                .collect();
+            let mut text_list =
+                TranslatableTextList::new(call["target_language"].as_str().unwrap(), texts);
+            if let Some(source_language) = call["source_language"].as_str() {
+                text_list = text_list.with_source_language(source_language);
+            }
+            let options = call.get("options").map(options_from_json);
+            deepl.translate(options, text_list).map(|translations| {
+                translations
+                    .into_iter()
+                    .map(|t| {
+                        json!({
+                            "detected_source_language": t.detected_source_language,
+                            "text": t.text,
+                            "billed_characters": t.billed_characters,
+                        })
+                    })
+                    .collect()
+            })
+        }
+        endpoint => panic!("{}: unknown endpoint {}", name, endpoint),
+    };
+    let result = result.unwrap_or_else(|e| json!({ "error": error_to_string(&e) }));
+    assert_eq!(result, case["result"], "{}: unexpected result", name);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1, "{}: unexpected number of requests", name);
+    let expected = &case["request"];
+    assert_eq!(requests[0].method, expected["method"], "{}: method", name);
+    assert_eq!(requests[0].path, expected["path"], "{}: path", name);
+    assert_eq!(requests[0].body, expected["body"], "{}: body", name);
+    assert_eq!(
+        requests[0].header("authorization"),
+        expected["authorization"].as_str(),
+        "{}: authorization header",
+        name
+    );
+}
+
+fn languages_to_json(languages: LanguageList) -> Value {
+    languages
+        .into_iter()
+        .map(|l| {
+            json!({
+                "language": l.language,
+                "name": l.name,
+                "supports_formality": l.supports_formality,
+            })
+        })
+        .collect()
+}
+
+fn options_from_json(options: &Value) -> TranslationOptions {
+    let mut result = TranslationOptions::new();
+    if let Some(value) = options["split_sentences"].as_str() {
+        result = result.split_sentences(match value {
+            "none" => SplitSentences::None,
+            "punctuation" => SplitSentences::Punctuation,
+            "punctuation_and_newlines" => SplitSentences::PunctuationAndNewlines,
+            value => panic!("unknown split_sentences {}", value),
+        });
+    }
+    if let Some(value) = options["preserve_formatting"].as_bool() {
+        result = result.preserve_formatting(value);
+    }
+    if let Some(value) = options["formality"].as_str() {
+        result = result.formality(match value {
+            "default" => Formality::Default,
+            "more" => Formality::More,
+            "less" => Formality::Less,
+            value => panic!("unknown formality {}", value),
+        });
+    }
+    if let Some(value) = options["model_type"].as_str() {
+        result = result.model_type(match value {
+            "latency_optimized" => ModelType::LatencyOptimized,
+            "quality_optimized" => ModelType::QualityOptimized,
+            "prefer_quality_optimized" => ModelType::PreferQualityOptimized,
+            value => panic!("unknown model_type {}", value),
+        });
+    }
+    if let Some(value) = options["show_billed_characters"].as_bool() {
+        result = result.show_billed_characters(value);
+    }
+    if let Some(value) = options["tag_handling"].as_str() {
+        result = result.tag_handling(match value {
+            "html" => TagHandling::Html,
+            "xml" => TagHandling::Xml,
+            value => panic!("unknown tag_handling {}", value),
+        });
+    }
+    if let Some(values) = options["ignore_tags"].as_array() {
+        result = result.ignore_tags(values.iter().filter_map(Value::as_str));
+    }
+    result
+}
+
+fn error_to_string(error: &Error) -> String {
+    match error {
+        Error::AuthorizationError => "authorization".to_string(),
+        Error::QuotaExceeded => "quota_exceeded".to_string(),
+        Error::TooManyRequests { .. } => "too_many_requests".to_string(),
+        Error::DeserializationError => "deserialization".to_string(),
+        Error::ServerError { message, .. } => format!("server: {}", message),
+        error => panic!("unexpected error {:?}", error),
+    }
+}
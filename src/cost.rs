@@ -0,0 +1,108 @@
+//! Estimation of translation costs before sending anything, e. g. for budgeting tools.
+//!
+//! DeepL bills the characters of the source texts, counted as Unicode characters, regardless of
+//! the target language. Whitespace and markup count as well, and empty texts are free.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::cost::estimate_cost;
+//!
+//! // 25 EUR per million characters.
+//! let estimate = estimate_cost(&["Hello", "Grüße"], 25.0);
+//! assert_eq!(estimate.characters, 10);
+//! assert_eq!(estimate.cost, 0.00025);
+//! ```
+
+use crate::{TranslatableTextList, MAX_TEXTS_PER_REQUEST};
+
+/// The estimated cost of translating some texts.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CostEstimate {
+    /// Number of characters that will be billed.
+    pub characters: u64,
+    /// Estimated cost, in the currency of the price.
+    pub cost: f64,
+}
+
+impl CostEstimate {
+    fn new(characters: u64, price_per_million_chars: f64) -> CostEstimate {
+        CostEstimate {
+            characters,
+            cost: characters as f64 * price_per_million_chars / 1_000_000.0,
+        }
+    }
+}
+
+/// Count the characters of `text` the way DeepL bills them: every Unicode scalar value counts
+/// as one character, regardless of how many bytes it takes in UTF-8. With
+/// [tag handling](crate::TranslationOptions), markup is billed as well, so pass the text
+/// exactly as it is sent.
+///
+/// ```rust
+/// use deepl_api::billable_characters;
+///
+/// assert_eq!(billable_characters("Grüße"), 5);
+/// assert_eq!("Grüße".len(), 7);
+/// // Combining characters count separately.
+/// assert_eq!(billable_characters("e\u{301}"), 2);
+/// ```
+pub fn billable_characters(text: &str) -> u64 {
+    text.chars().count() as u64
+}
+
+/// Estimate the cost of translating `texts` into one target language, given the price per
+/// million characters of the account's plan.
+pub fn estimate_cost<S: AsRef<str>>(texts: &[S], price_per_million_chars: f64) -> CostEstimate {
+    let characters = texts
+        .iter()
+        .map(|text| billable_characters(text.as_ref()))
+        .sum();
+    CostEstimate::new(characters, price_per_million_chars)
+}
+
+/// Estimate the cost of each request [DeepL::translate](crate::DeepL::translate) sends for
+/// `text_list`, assuming the default [chunking](crate::DeepL::with_auto_chunking) into
+/// requests of at most [MAX_TEXTS_PER_REQUEST] texts.
+pub fn estimate_request_costs(
+    text_list: &TranslatableTextList,
+    price_per_million_chars: f64,
+) -> Vec<CostEstimate> {
+    text_list
+        .texts
+        .chunks(MAX_TEXTS_PER_REQUEST)
+        .map(|chunk| estimate_cost(chunk, price_per_million_chars))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Language;
+
+    #[test]
+    fn billable_character_counts() {
+        assert_eq!(billable_characters(""), 0);
+        assert_eq!(billable_characters("日本語"), 3);
+        assert_eq!(billable_characters("<b>Hi</b> 👋"), 11);
+    }
+
+    #[test]
+    fn cost_estimates() {
+        let estimate = estimate_cost(&["日本語", " <b>x</b> ", ""], 20.0);
+        assert_eq!(estimate.characters, 13);
+        assert!((estimate.cost - 0.00026).abs() < 1e-12);
+        assert_eq!(estimate_cost::<&str>(&[], 20.0), CostEstimate::default());
+
+        let text_list = TranslatableTextList::new(Language::De, vec!["ab"; 120]);
+        let estimates = estimate_request_costs(&text_list, 1_000_000.0);
+        assert_eq!(
+            estimates
+                .iter()
+                .map(|estimate| estimate.characters)
+                .collect::<Vec<_>>(),
+            vec![100, 100, 40]
+        );
+        assert_eq!(estimates[2].cost, 40.0);
+    }
+}
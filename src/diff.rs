@@ -0,0 +1,195 @@
+//! Segment-level diffing of a source text against a previous version of it.
+//!
+//! When a translated document is updated, usually only a few of its segments (sentences,
+//! lines or messages) change. [align_segments] aligns the previous and the current source
+//! segments and classifies each of them, so that only [changed or added](SegmentChange::needs_translation)
+//! segments have to be sent to DeepL again, while the existing translations of unchanged
+//! segments can be reused.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::diff::*;
+//!
+//! let previous = vec!["Hello.", "How are you?", "Goodbye."];
+//! let current = vec!["Hello.", "How are you doing?", "See you!", "Goodbye."];
+//!
+//! let changes = align_segments(&previous, &current);
+//! assert_eq!(
+//!     changes,
+//!     vec![
+//!         SegmentChange::Unchanged { old_index: 0, new_index: 0 },
+//!         SegmentChange::Changed { old_index: 1, new_index: 1 },
+//!         SegmentChange::Added { new_index: 2 },
+//!         SegmentChange::Unchanged { old_index: 2, new_index: 3 },
+//!     ]
+//! );
+//! ```
+
+/// Classification of a segment when comparing two versions of a source text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SegmentChange {
+    /// The segment exists unmodified in both versions.
+    Unchanged { old_index: usize, new_index: usize },
+    /// The segment was modified. `old_index` refers to the segment it replaces.
+    Changed { old_index: usize, new_index: usize },
+    /// The segment only exists in the current version.
+    Added { new_index: usize },
+    /// The segment only exists in the previous version.
+    Removed { old_index: usize },
+}
+
+impl SegmentChange {
+    /// Whether the segment of the current version has to be (re-)translated.
+    pub fn needs_translation(&self) -> bool {
+        matches!(
+            self,
+            SegmentChange::Changed { .. } | SegmentChange::Added { .. }
+        )
+    }
+
+    /// Index of the segment in the previous version, if it exists there.
+    pub fn old_index(&self) -> Option<usize> {
+        match *self {
+            SegmentChange::Unchanged { old_index, .. }
+            | SegmentChange::Changed { old_index, .. }
+            | SegmentChange::Removed { old_index } => Some(old_index),
+            SegmentChange::Added { .. } => None,
+        }
+    }
+
+    /// Index of the segment in the current version, if it exists there.
+    pub fn new_index(&self) -> Option<usize> {
+        match *self {
+            SegmentChange::Unchanged { new_index, .. }
+            | SegmentChange::Changed { new_index, .. }
+            | SegmentChange::Added { new_index } => Some(new_index),
+            SegmentChange::Removed { .. } => None,
+        }
+    }
+}
+
+/// Aligns the segments of two versions of a source text.
+///
+/// Identical segments are matched based on their longest common subsequence. Between two
+/// matches, the remaining segments are paired up in order as [Changed](SegmentChange::Changed),
+/// and any surplus is classified as [Added](SegmentChange::Added) or [Removed](SegmentChange::Removed).
+///
+/// The result lists all segments in document order. Needs `O(old.len() * new.len())` memory.
+pub fn align_segments<S: AsRef<str>, T: AsRef<str>>(old: &[S], new: &[T]) -> Vec<SegmentChange> {
+    // lcs[i][j] holds the length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match old[i].as_ref() == new[j].as_ref() {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i].as_ref() == new[j].as_ref() {
+            flush_gap(&mut changes, &mut removed, &mut added);
+            changes.push(SegmentChange::Unchanged {
+                old_index: i,
+                new_index: j,
+            });
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            added.push(j);
+            j += 1;
+        } else {
+            removed.push(i);
+            i += 1;
+        }
+    }
+    flush_gap(&mut changes, &mut removed, &mut added);
+    changes
+}
+
+/// Pairs up the removed and added segments between two matches.
+fn flush_gap(changes: &mut Vec<SegmentChange>, removed: &mut Vec<usize>, added: &mut Vec<usize>) {
+    let paired = removed.len().min(added.len());
+    for (&old_index, &new_index) in removed.iter().zip(added.iter()) {
+        changes.push(SegmentChange::Changed {
+            old_index,
+            new_index,
+        });
+    }
+    for &new_index in &added[paired..] {
+        changes.push(SegmentChange::Added { new_index });
+    }
+    for &old_index in &removed[paired..] {
+        changes.push(SegmentChange::Removed { old_index });
+    }
+    removed.clear();
+    added.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_segments() {
+        let empty: Vec<&str> = vec![];
+        assert_eq!(super::align_segments(&empty, &empty), vec![]);
+
+        assert_eq!(
+            super::align_segments(&empty, &["a", "b"]),
+            vec![
+                SegmentChange::Added { new_index: 0 },
+                SegmentChange::Added { new_index: 1 },
+            ]
+        );
+
+        assert_eq!(
+            super::align_segments(&["a", "b", "c"], &["a", "c"]),
+            vec![
+                SegmentChange::Unchanged {
+                    old_index: 0,
+                    new_index: 0
+                },
+                SegmentChange::Removed { old_index: 1 },
+                SegmentChange::Unchanged {
+                    old_index: 2,
+                    new_index: 1
+                },
+            ]
+        );
+
+        let changes = super::align_segments(&["a", "b", "c", "d"], &["x", "b", "y", "z"]);
+        assert_eq!(
+            changes,
+            vec![
+                SegmentChange::Changed {
+                    old_index: 0,
+                    new_index: 0
+                },
+                SegmentChange::Unchanged {
+                    old_index: 1,
+                    new_index: 1
+                },
+                SegmentChange::Changed {
+                    old_index: 2,
+                    new_index: 2
+                },
+                SegmentChange::Changed {
+                    old_index: 3,
+                    new_index: 3
+                },
+            ]
+        );
+        let to_translate: Vec<usize> = changes
+            .iter()
+            .filter(|c| c.needs_translation())
+            .filter_map(|c| c.new_index())
+            .collect();
+        assert_eq!(to_translate, vec![0, 2, 3]);
+    }
+}
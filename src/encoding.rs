@@ -0,0 +1,153 @@
+//! Transcoding of translations into legacy output encodings.
+//!
+//! DeepL always responds in UTF-8. Some target formats require other encodings though, e. g.
+//! Shift_JIS for the properties files of a legacy Japanese product. [OutputEncodings] maps target
+//! languages to [encoding labels](https://encoding.spec.whatwg.org/#names-and-labels) and encodes
+//! translations accordingly.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::encoding::OutputEncodings;
+//! use deepl_api::Language;
+//!
+//! let encodings = OutputEncodings::new()
+//!     .with_encoding(Language::Ja, "shift_jis")
+//!     .unwrap();
+//! assert_eq!(encodings.encode(Language::Ja, "日本").unwrap(), vec![0x93, 0xfa, 0x96, 0x7b]);
+//! assert_eq!(encodings.encode(Language::De, "ä").unwrap(), "ä".as_bytes());
+//! ```
+
+use crate::{Language, Result};
+use encoding_rs::Encoding;
+use std::collections::HashMap;
+use std::io;
+
+/// Output encodings per target language, with a default for all other languages.
+#[derive(Clone, Debug)]
+pub struct OutputEncodings {
+    default: &'static Encoding,
+    per_language: HashMap<Language, &'static Encoding>,
+}
+
+impl Default for OutputEncodings {
+    fn default() -> OutputEncodings {
+        OutputEncodings {
+            default: encoding_rs::UTF_8,
+            per_language: HashMap::new(),
+        }
+    }
+}
+
+impl OutputEncodings {
+    /// Create a mapping that encodes all languages in UTF-8.
+    pub fn new() -> OutputEncodings {
+        OutputEncodings::default()
+    }
+
+    /// Set the encoding for all languages without a specific one. Fails for unknown labels.
+    pub fn with_default(mut self, label: &str) -> Result<OutputEncodings> {
+        self.default = lookup(label)?;
+        Ok(self)
+    }
+
+    /// Set the encoding for `target_language`. Fails for unknown labels.
+    pub fn with_encoding(
+        mut self,
+        target_language: impl Into<Language>,
+        label: &str,
+    ) -> Result<OutputEncodings> {
+        self.per_language
+            .insert(target_language.into(), lookup(label)?);
+        Ok(self)
+    }
+
+    /// The canonical name of the encoding used for `target_language`, e. g. `Shift_JIS`.
+    pub fn encoding_name(&self, target_language: impl Into<Language>) -> &'static str {
+        self.encoding(&target_language.into()).name()
+    }
+
+    /// Encode a translation into `target_language` with the configured encoding.
+    ///
+    /// Fails if the text contains characters that cannot be represented in the encoding.
+    pub fn encode(&self, target_language: impl Into<Language>, text: &str) -> Result<Vec<u8>> {
+        encode_with(self.encoding(&target_language.into()), text)
+    }
+
+    fn encoding(&self, target_language: &Language) -> &'static Encoding {
+        self.per_language
+            .get(target_language)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Encode `text` with the encoding of the given label.
+///
+/// Fails for unknown labels and if the text contains characters that cannot be represented in
+/// the encoding. As per the WHATWG encoding standard, the UTF-16 labels produce UTF-8.
+pub fn encode(label: &str, text: &str) -> Result<Vec<u8>> {
+    encode_with(lookup(label)?, text)
+}
+
+fn lookup(label: &str) -> Result<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown encoding '{}'", label),
+        )
+        .into()
+    })
+}
+
+fn encode_with(encoding: &'static Encoding, text: &str) -> Result<Vec<u8>> {
+    let (bytes, _, had_errors) = encoding.encode(text);
+    if had_errors {
+        // Find the culprit for a helpful message.
+        let mut buffer = [0; 4];
+        let character = text
+            .chars()
+            .find(|c| encoding.encode(c.encode_utf8(&mut buffer)).2)
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the character '{}' cannot be represented in {}",
+                character,
+                encoding.name()
+            ),
+        )
+        .into());
+    }
+    Ok(bytes.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_encodings() {
+        let encodings = OutputEncodings::new()
+            .with_default("latin1")
+            .unwrap()
+            .with_encoding("ja", "Shift_JIS")
+            .unwrap();
+        assert_eq!(encodings.encoding_name("JA"), "Shift_JIS");
+        assert_eq!(encodings.encoding_name(Language::De), "windows-1252");
+        assert_eq!(encodings.encode("DE", "Grüße").unwrap(), b"Gr\xfc\xdfe");
+
+        let error = encodings.encode("DE", "Grüße 日本").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the character '日' cannot be represented in windows-1252"
+        );
+
+        assert_eq!(encode("utf-8", "日本").unwrap(), "日本".as_bytes());
+        assert_eq!(
+            encode("klingon", "Qapla'").unwrap_err().to_string(),
+            "unknown encoding 'klingon'"
+        );
+        assert!(OutputEncodings::new().with_default("klingon").is_err());
+    }
+}
@@ -0,0 +1,47 @@
+//! Translation of selected fields of structs, see [DeepL::translate_struct](crate::DeepL::translate_struct).
+
+/// Types with string fields that can be translated in place by
+/// [DeepL::translate_struct](crate::DeepL::translate_struct).
+///
+/// With the `derive` feature, the trait can be derived by annotating the fields with `#[translate]`:
+///
+/// ```rust,ignore
+/// use deepl_api::Translate;
+///
+/// #[derive(Translate)]
+/// struct Product {
+///     sku: String,
+///     #[translate]
+///     title: String,
+///     #[translate]
+///     description: Option<String>,
+/// }
+/// ```
+pub trait Translate {
+    /// Mutable references to all fields that should be translated.
+    fn translatable_fields(&mut self) -> Vec<&mut String>;
+}
+
+/// Field types supported by `#[derive(Translate)]`.
+pub trait TranslatableField {
+    /// Add the strings of this field to `fields`.
+    fn collect_fields<'a>(&'a mut self, fields: &mut Vec<&'a mut String>);
+}
+
+impl TranslatableField for String {
+    fn collect_fields<'a>(&'a mut self, fields: &mut Vec<&'a mut String>) {
+        fields.push(self);
+    }
+}
+
+impl TranslatableField for Option<String> {
+    fn collect_fields<'a>(&'a mut self, fields: &mut Vec<&'a mut String>) {
+        fields.extend(self.as_mut());
+    }
+}
+
+impl TranslatableField for Vec<String> {
+    fn collect_fields<'a>(&'a mut self, fields: &mut Vec<&'a mut String>) {
+        fields.extend(self.iter_mut());
+    }
+}
@@ -0,0 +1,836 @@
+//! Multilingual glossaries, which hold the entries for several language pairs in one glossary.
+//!
+//! A [MultilingualGlossary] consists of one dictionary per language pair. Glossaries are managed
+//! with the glossary methods of [DeepL](crate::DeepL), which use DeepL's `/v3/glossaries`
+//! endpoints, and are applied to translations with
+//! [TranslationOptions::glossary](crate::TranslationOptions::glossary). The entries of a
+//! dictionary are tab-separated, one source term and its translation per line. Use
+//! [GlossaryEntries] to read them from TSV or CSV files and check them before uploading.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use deepl_api::glossary::GlossaryDictionary;
+//! use deepl_api::*;
+//!
+//! let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap(), false);
+//! let glossary = deepl
+//!     .create_glossary(
+//!         "Product names",
+//!         &[
+//!             GlossaryDictionary::new(Language::En, Language::De, "Widget\tWidget"),
+//!             GlossaryDictionary::new(Language::En, Language::Fr, "Widget\tGadget"),
+//!         ],
+//!     )
+//!     .unwrap();
+//!
+//! let options = TranslationOptions::new().glossary(glossary.glossary_id);
+//! let texts = TranslatableTextList::new(Language::De, vec!["The Widget is broken."])
+//!     .with_source_language(Language::En);
+//! deepl.translate(Some(options), texts).unwrap();
+//! ```
+
+use crate::{form_encode, parse_response, DeepL, Error, Language, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Instant;
+
+/// Maximum size of the entries of a glossary in bytes, as accepted by DeepL.
+pub const MAX_GLOSSARY_SIZE: usize = 10 * 1024 * 1024;
+
+/// A glossary stored at DeepL, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[non_exhaustive]
+pub struct MultilingualGlossary {
+    /// Identifies the glossary, e. g. for [TranslationOptions::glossary](crate::TranslationOptions::glossary).
+    pub glossary_id: String,
+    /// Name of the glossary.
+    pub name: String,
+    /// One entry per language pair.
+    pub dictionaries: Vec<DictionaryInfo>,
+    /// Time of creation in ISO 8601 format.
+    pub creation_time: String,
+}
+
+/// The language pair and size of one dictionary of a [MultilingualGlossary].
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[non_exhaustive]
+pub struct DictionaryInfo {
+    /// Language of the source terms.
+    pub source_lang: String,
+    /// Language of the translations.
+    pub target_lang: String,
+    /// Number of entries.
+    pub entry_count: u64,
+}
+
+/// The entries of a glossary for one language pair.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct GlossaryDictionary {
+    /// Language of the source terms.
+    pub source_lang: String,
+    /// Language of the translations.
+    pub target_lang: String,
+    /// Tab-separated entries, one source term and its translation per line.
+    pub entries: String,
+}
+
+impl GlossaryDictionary {
+    /// Create a dictionary from tab-separated `entries`.
+    pub fn new(
+        source_language: impl Into<Language>,
+        target_language: impl Into<Language>,
+        entries: impl Into<String>,
+    ) -> GlossaryDictionary {
+        GlossaryDictionary {
+            source_lang: source_language.into().into(),
+            target_lang: target_language.into().into(),
+            entries: entries.into(),
+        }
+    }
+
+    /// Create a dictionary from validated `entries`.
+    pub fn from_entries(
+        source_language: impl Into<Language>,
+        target_language: impl Into<Language>,
+        entries: &GlossaryEntries,
+    ) -> GlossaryDictionary {
+        GlossaryDictionary::new(source_language, target_language, entries.to_tsv())
+    }
+
+    /// Private method that builds the JSON representation of the API.
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "source_lang": self.source_lang,
+            "target_lang": self.target_lang,
+            "entries": self.entries,
+            "entries_format": "tsv",
+        })
+    }
+}
+
+/// Glossary entries that satisfy DeepL's constraints: source and target terms are neither empty
+/// nor contain control characters or line breaks, source terms are unique, and all entries
+/// together don't exceed [MAX_GLOSSARY_SIZE]. Leading and trailing whitespace is removed from
+/// the terms.
+///
+/// Violations are reported as [InvalidGlossary](Error::InvalidGlossary) errors with the line of
+/// the offending entry.
+///
+/// ```rust
+/// use deepl_api::glossary::GlossaryEntries;
+///
+/// let entries = GlossaryEntries::from_csv("Widget,Widget\n\"screw, small\",Schräubchen").unwrap();
+/// assert_eq!(entries.to_tsv(), "Widget\tWidget\nscrew, small\tSchräubchen");
+///
+/// let error = GlossaryEntries::from_tsv("Widget\tWidget\nWidget\tGadget").unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "Invalid glossary entry on line 2: duplicate source term \"Widget\", first on line 1."
+/// );
+/// ```
+///
+/// Entries can also be built from maps and iterators, e. g. when syncing terminology from a
+/// termbase, and combined with [merge](GlossaryEntries::merge) and
+/// [diff](GlossaryEntries::diff):
+///
+/// ```rust
+/// use deepl_api::glossary::GlossaryEntries;
+///
+/// let current = GlossaryEntries::from_iter(vec![("Widget", "Widget"), ("screw", "Schraube")]).unwrap();
+/// let termbase = GlossaryEntries::from_iter(vec![("Widget", "Bauteil"), ("nut", "Mutter")]).unwrap();
+///
+/// let diff = current.diff(&termbase);
+/// assert_eq!(diff.added, vec![("nut".to_string(), "Mutter".to_string())]);
+/// assert_eq!(diff.removed, vec![("screw".to_string(), "Schraube".to_string())]);
+///
+/// let mut merged = current.clone();
+/// merged.merge(&termbase).unwrap();
+/// assert_eq!(merged.to_tsv(), "Widget\tBauteil\nscrew\tSchraube\nnut\tMutter");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GlossaryEntries {
+    entries: Vec<(String, String)>,
+    // The index in `entries` and the input line of each source term.
+    terms: HashMap<String, (usize, usize)>,
+    size: usize,
+}
+
+/// The differences between two [GlossaryEntries], see [GlossaryEntries::diff].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct GlossaryDiff {
+    /// Entries whose source term is only in the other entries.
+    pub added: Vec<(String, String)>,
+    /// Entries whose source term is missing in the other entries.
+    pub removed: Vec<(String, String)>,
+    /// Source terms with a different target term, as source, old and new target term.
+    pub changed: Vec<(String, String, String)>,
+}
+
+impl GlossaryDiff {
+    /// Whether the entries are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl GlossaryEntries {
+    /// Create an empty list of entries.
+    pub fn new() -> GlossaryEntries {
+        GlossaryEntries::default()
+    }
+
+    /// Parse tab-separated entries, one per line. Blank lines are skipped.
+    pub fn from_tsv(tsv: &str) -> Result<GlossaryEntries> {
+        let mut entries = GlossaryEntries::new();
+        for (index, line) in tsv.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (source, target) = line
+                .split_once('\t')
+                .ok_or_else(|| invalid(index + 1, "missing tab between source and target term"))?;
+            entries.insert(index + 1, source, target)?;
+        }
+        Ok(entries)
+    }
+
+    /// Parse comma-separated entries with a source and a target column, as described in
+    /// [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180). Fields that contain commas or quotes
+    /// must be quoted. Blank lines are skipped.
+    pub fn from_csv(csv: &str) -> Result<GlossaryEntries> {
+        let mut entries = GlossaryEntries::new();
+        for (line, record) in csv_records(csv)? {
+            match record.as_slice() {
+                [field] if field.trim().is_empty() => continue,
+                [source, target] => entries.insert(line, source, target)?,
+                fields => {
+                    let reason = format!("expected 2 columns, found {}", fields.len());
+                    return Err(invalid(line, &reason));
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Build entries from pairs of source and target term. Unlike [FromIterator](std::iter::FromIterator), this validates
+    /// the entries and can fail; the line in errors is the position of the pair.
+    pub fn from_iter<I, S, T>(iter: I) -> Result<GlossaryEntries>
+    where
+        I: IntoIterator<Item = (S, T)>,
+        S: AsRef<str>,
+        T: AsRef<str>,
+    {
+        let mut entries = GlossaryEntries::new();
+        for (source, target) in iter {
+            entries.add(source.as_ref(), target.as_ref())?;
+        }
+        Ok(entries)
+    }
+
+    /// Add an entry. Fails if it violates DeepL's constraints; the line in the error is the
+    /// position of the entry.
+    pub fn add(&mut self, source: &str, target: &str) -> Result<()> {
+        self.insert(self.entries.len() + 1, source, target)
+    }
+
+    /// The entries as pairs of source and target term, in the order they were added.
+    pub fn entries(&self) -> &[(String, String)] {
+        &self.entries
+    }
+
+    /// The target term for `source`, if there is an entry.
+    pub fn get(&self, source: &str) -> Option<&str> {
+        self.terms
+            .get(source)
+            .map(|&(index, _)| self.entries[index].1.as_str())
+    }
+
+    /// Add the entries of `other`, replacing the target terms of source terms that are in both.
+    /// Fails without changes if the result would exceed [MAX_GLOSSARY_SIZE]; the line in the error
+    /// is the line of the entry in `other`.
+    pub fn merge(&mut self, other: &GlossaryEntries) -> Result<()> {
+        let (mut size, mut exceeded) = (self.size, None);
+        for (source, target) in &other.entries {
+            size += target.len();
+            match self.get(source) {
+                Some(old) => size -= old.len(),
+                None => size += source.len() + 2,
+            }
+            if size > MAX_GLOSSARY_SIZE && exceeded.is_none() {
+                exceeded = Some(other.terms[source].1);
+            }
+        }
+        if let (true, Some(line)) = (size > MAX_GLOSSARY_SIZE, exceeded) {
+            return Err(too_large(line));
+        }
+        for (source, target) in &other.entries {
+            match self.terms.get(source) {
+                Some(&(index, _)) => self.entries[index].1 = target.clone(),
+                None => {
+                    let line = self.entries.len() + 1;
+                    self.terms
+                        .insert(source.clone(), (self.entries.len(), line));
+                    self.entries.push((source.clone(), target.clone()));
+                }
+            }
+        }
+        self.size = size;
+        Ok(())
+    }
+
+    /// The changes that turn these entries into `other`, in the order of the entries.
+    pub fn diff(&self, other: &GlossaryEntries) -> GlossaryDiff {
+        let mut diff = GlossaryDiff::default();
+        for (source, target) in &self.entries {
+            match other.get(source) {
+                None => diff.removed.push((source.clone(), target.clone())),
+                Some(new) if new != target => {
+                    diff.changed
+                        .push((source.clone(), target.clone(), new.to_string()))
+                }
+                Some(_) => {}
+            }
+        }
+        diff.added = other
+            .entries
+            .iter()
+            .filter(|(source, _)| !self.terms.contains_key(source))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// Number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serialize the entries in the tab-separated format of the glossary endpoints.
+    pub fn to_tsv(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(source, target)| format!("{}\t{}", source, target))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Private method that validates and adds an entry from `line`.
+    fn insert(&mut self, line: usize, source: &str, target: &str) -> Result<()> {
+        let (source, target) = (source.trim(), target.trim());
+        validate_term(line, "source", source)?;
+        validate_term(line, "target", target)?;
+        if let Some((_, first)) = self.terms.get(source) {
+            let reason = format!(
+                "duplicate source term \"{}\", first on line {}",
+                source, first
+            );
+            return Err(invalid(line, &reason));
+        }
+        // Each entry takes a tab and a line break.
+        let size = self.size + source.len() + target.len() + 2;
+        if size > MAX_GLOSSARY_SIZE {
+            return Err(too_large(line));
+        }
+        self.size = size;
+        self.terms
+            .insert(source.to_string(), (self.entries.len(), line));
+        self.entries.push((source.to_string(), target.to_string()));
+        Ok(())
+    }
+}
+
+/// Entries are equal if they have the same terms in the same order, regardless of their lines.
+impl PartialEq for GlossaryEntries {
+    fn eq(&self, other: &GlossaryEntries) -> bool {
+        self.entries == other.entries
+    }
+}
+
+/// Converts a map, with the entries sorted by source term. Fails like
+/// [from_iter](GlossaryEntries::from_iter).
+impl TryFrom<HashMap<String, String>> for GlossaryEntries {
+    type Error = Error;
+
+    fn try_from(map: HashMap<String, String>) -> Result<GlossaryEntries> {
+        let mut entries: Vec<_> = map.into_iter().collect();
+        entries.sort();
+        GlossaryEntries::from_iter(entries)
+    }
+}
+
+impl<'a> IntoIterator for &'a GlossaryEntries {
+    type Item = &'a (String, String);
+    type IntoIter = std::slice::Iter<'a, (String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+fn too_large(line: usize) -> Error {
+    let reason = format!("the entries exceed {} bytes", MAX_GLOSSARY_SIZE);
+    invalid(line, &reason)
+}
+
+fn invalid(line: usize, reason: &str) -> Error {
+    Error::InvalidGlossary {
+        line,
+        reason: reason.to_string(),
+    }
+}
+
+/// Private helper that checks a glossary term, see [GlossaryEntries].
+fn validate_term(line: usize, kind: &str, term: &str) -> Result<()> {
+    if term.is_empty() {
+        return Err(invalid(line, &format!("empty {} term", kind)));
+    }
+    // C0 and C1 control characters, including tabs and line breaks, and Unicode line breaks.
+    let forbidden = |c: char| c.is_control() || c == '\u{2028}' || c == '\u{2029}';
+    match term.chars().find(|&c| forbidden(c)) {
+        Some(c) => Err(invalid(
+            line,
+            &format!(
+                "{} term contains the control character U+{:04X}",
+                kind, c as u32
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Private helper that splits CSV into records, each with the line it starts on.
+fn csv_records(csv: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut chars = csv.chars().peekable();
+    let mut line = 1;
+    while chars.peek().is_some() {
+        let start = line;
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        loop {
+            match chars.next() {
+                Some('"') if quoted => match chars.peek() {
+                    Some('"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    _ => quoted = false,
+                },
+                Some('"') if field.is_empty() => quoted = true,
+                Some('\n') if quoted => {
+                    line += 1;
+                    field.push('\n');
+                }
+                Some(',') if !quoted => record.push(std::mem::take(&mut field)),
+                Some('\r') if !quoted && chars.peek() == Some(&'\n') => {}
+                Some('\n') | None if !quoted => {
+                    line += 1;
+                    break;
+                }
+                None => return Err(invalid(start, "unterminated quoted field")),
+                Some(c) => field.push(c),
+            }
+        }
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+// Only needed for JSON deserialization.
+#[derive(Deserialize)]
+struct GlossaryList {
+    glossaries: Vec<MultilingualGlossary>,
+}
+
+// Only needed for JSON deserialization.
+#[derive(Deserialize)]
+struct DictionaryList {
+    dictionaries: Vec<GlossaryDictionary>,
+}
+
+/// Glossary management with the `/v3/glossaries` endpoints, see the
+/// [module documentation](self).
+impl DeepL {
+    /// Create a glossary with one dictionary per language pair.
+    pub fn create_glossary(
+        &self,
+        name: &str,
+        dictionaries: &[GlossaryDictionary],
+    ) -> Result<MultilingualGlossary> {
+        let body = json!({
+            "name": name,
+            "dictionaries": dictionaries.iter().map(GlossaryDictionary::to_json).collect::<Vec<_>>(),
+        });
+        self.glossary_request(reqwest::Method::POST, "", &[], Some(&body))
+            .and_then(parse_response)
+    }
+
+    /// Retrieve all glossaries of the account, without their entries.
+    pub fn glossaries(&self) -> Result<Vec<MultilingualGlossary>> {
+        self.glossary_request(reqwest::Method::GET, "", &[], None)
+            .and_then(parse_response::<GlossaryList>)
+            .map(|list| list.glossaries)
+    }
+
+    /// Retrieve one glossary, without its entries.
+    pub fn glossary(&self, glossary_id: &str) -> Result<MultilingualGlossary> {
+        let path = format!("/{}", form_encode(glossary_id));
+        self.glossary_request(reqwest::Method::GET, &path, &[], None)
+            .and_then(parse_response)
+    }
+
+    /// Rename a glossary and/or add `dictionaries`. The entries of dictionaries for language pairs
+    /// that already exist are merged into them; entries with the same source term are replaced.
+    pub fn update_glossary(
+        &self,
+        glossary_id: &str,
+        name: Option<&str>,
+        dictionaries: &[GlossaryDictionary],
+    ) -> Result<MultilingualGlossary> {
+        let mut body = json!({});
+        if let Some(name) = name {
+            body["name"] = json!(name);
+        }
+        if !dictionaries.is_empty() {
+            body["dictionaries"] = dictionaries
+                .iter()
+                .map(GlossaryDictionary::to_json)
+                .collect();
+        }
+        let path = format!("/{}", form_encode(glossary_id));
+        self.glossary_request(reqwest::Method::PATCH, &path, &[], Some(&body))
+            .and_then(parse_response)
+    }
+
+    /// Replace all entries of the dictionary for the language pair of `dictionary`, or add it to
+    /// the glossary if there is none.
+    pub fn replace_dictionary(
+        &self,
+        glossary_id: &str,
+        dictionary: &GlossaryDictionary,
+    ) -> Result<DictionaryInfo> {
+        let path = format!("/{}/dictionaries", form_encode(glossary_id));
+        let body = dictionary.to_json();
+        self.glossary_request(reqwest::Method::PUT, &path, &[], Some(&body))
+            .and_then(parse_response)
+    }
+
+    /// Retrieve the entries of a glossary for one language pair.
+    pub fn glossary_entries(
+        &self,
+        glossary_id: &str,
+        source_language: impl Into<Language>,
+        target_language: impl Into<Language>,
+    ) -> Result<GlossaryDictionary> {
+        let path = format!("/{}/entries", form_encode(glossary_id));
+        let query = language_pair(source_language, target_language);
+        self.glossary_request(reqwest::Method::GET, &path, &query, None)
+            .and_then(parse_response::<DictionaryList>)
+            .and_then(|list| {
+                list.dictionaries
+                    .into_iter()
+                    .next()
+                    .ok_or(Error::DeserializationError)
+            })
+    }
+
+    /// Remove the dictionary for one language pair from a glossary.
+    pub fn delete_dictionary(
+        &self,
+        glossary_id: &str,
+        source_language: impl Into<Language>,
+        target_language: impl Into<Language>,
+    ) -> Result<()> {
+        let path = format!("/{}/dictionaries", form_encode(glossary_id));
+        let query = language_pair(source_language, target_language);
+        self.glossary_request(reqwest::Method::DELETE, &path, &query, None)
+            .map(|_| ())
+    }
+
+    /// Delete a glossary with all its dictionaries.
+    pub fn delete_glossary(&self, glossary_id: &str) -> Result<()> {
+        let path = format!("/{}", form_encode(glossary_id));
+        self.glossary_request(reqwest::Method::DELETE, &path, &[], None)
+            .map(|_| ())
+    }
+
+    /// Private method that performs a call to the glossary endpoints.
+    fn glossary_request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, String)],
+        json: Option<&serde_json::Value>,
+    ) -> Result<reqwest::blocking::Response> {
+        let started = Instant::now();
+        let method_name = match method {
+            reqwest::Method::GET => "GET",
+            reqwest::Method::PATCH => "PATCH",
+            reqwest::Method::PUT => "PUT",
+            reqwest::Method::DELETE => "DELETE",
+            _ => "POST",
+        };
+        let path = format!("/v3/glossaries{}", path);
+        let result = self.http_request_as(None, None, method, &path, query, json);
+        self.observe(
+            method_name,
+            "glossaries",
+            started,
+            0,
+            None,
+            result.as_ref().err(),
+        );
+        result
+    }
+}
+
+fn language_pair(
+    source_language: impl Into<Language>,
+    target_language: impl Into<Language>,
+) -> [(&'static str, String); 2] {
+    [
+        ("source_lang", source_language.into().into()),
+        ("target_lang", target_language.into().into()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::{TranslatableTextList, TranslationOptions};
+
+    const GLOSSARY: &str = r#"{
+        "glossary_id": "def3a26b",
+        "name": "Product names",
+        "dictionaries": [{"source_lang": "en", "target_lang": "de", "entry_count": 2}],
+        "creation_time": "2024-08-01T12:00:00.000Z"
+    }"#;
+
+    #[test]
+    fn multilingual_glossaries() {
+        let server = MockServer::start(|request| {
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/v3/glossaries") => {
+                    MockResponse::json(200, &format!(r#"{{"glossaries": [{}]}}"#, GLOSSARY))
+                }
+                ("GET", path) if path.starts_with("/v3/glossaries/def3a26b/entries") => {
+                    MockResponse::json(
+                        200,
+                        r#"{"dictionaries": [{"source_lang": "en", "target_lang": "de",
+                            "entries": "Widget\tWidget\nscrew\tSchraube", "entries_format": "tsv"}]}"#,
+                    )
+                }
+                ("PUT", _) => MockResponse::json(
+                    200,
+                    r#"{"source_lang": "en", "target_lang": "fr", "entry_count": 1}"#,
+                ),
+                ("DELETE", _) => MockResponse {
+                    status: 204,
+                    headers: Vec::new(),
+                    body: String::new(),
+                },
+                ("GET", "/v3/glossaries/missing") => {
+                    MockResponse::json(404, r#"{"message": "Glossary not found"}"#)
+                }
+                _ => MockResponse::json(201, GLOSSARY),
+            }
+        });
+        let deepl = server.client();
+
+        let dictionary = GlossaryDictionary::new(Language::En, Language::De, "Widget\tWidget");
+        let glossary = deepl
+            .create_glossary("Product names", &[dictionary])
+            .unwrap();
+        assert_eq!(glossary.glossary_id, "def3a26b");
+        assert_eq!(glossary.dictionaries[0].entry_count, 2);
+        assert_eq!(deepl.glossaries().unwrap(), vec![glossary.clone()]);
+        deepl
+            .update_glossary("def3a26b", Some("Products"), &[])
+            .unwrap();
+
+        let french = GlossaryDictionary::new(Language::En, Language::Fr, "Widget\tGadget");
+        let info = deepl.replace_dictionary("def3a26b", &french).unwrap();
+        assert_eq!(info.target_lang, "fr");
+
+        let entries = deepl
+            .glossary_entries("def3a26b", Language::En, Language::De)
+            .unwrap();
+        assert_eq!(entries.entries, "Widget\tWidget\nscrew\tSchraube");
+        deepl
+            .delete_dictionary("def3a26b", Language::En, Language::Fr)
+            .unwrap();
+        deepl.delete_glossary("def3a26b").unwrap();
+        assert!(matches!(
+            deepl.glossary("missing"),
+            Err(Error::ServerError { status: 404, .. })
+        ));
+
+        let requests = server.requests();
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(
+            body,
+            json!({"name": "Product names", "dictionaries": [{"source_lang": "EN",
+                "target_lang": "DE", "entries": "Widget\tWidget", "entries_format": "tsv"}]})
+        );
+        assert_eq!(
+            requests[0].header("authorization"),
+            Some("DeepL-Auth-Key mock-key")
+        );
+        assert_eq!(requests[2].method, "PATCH");
+        assert_eq!(requests[2].body, r#"{"name":"Products"}"#);
+        assert_eq!(
+            requests[5].path,
+            "/v3/glossaries/def3a26b/dictionaries?source_lang=EN&target_lang=FR"
+        );
+    }
+
+    #[test]
+    fn glossary_entries() {
+        let entries =
+            GlossaryEntries::from_tsv("Widget\tWidget\r\n\n  screw \tSchraube\n").unwrap();
+        assert_eq!(
+            entries.entries(),
+            [
+                ("Widget".to_string(), "Widget".to_string()),
+                ("screw".to_string(), "Schraube".to_string())
+            ]
+        );
+        assert_eq!(entries.get("screw"), Some("Schraube"));
+
+        let csv = "Widget,Widget\n\"nut, \"\"hex\"\"\",\"Mutter\nsechskant\"\nbolt,Bolzen";
+        let error = GlossaryEntries::from_csv(csv).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid glossary entry on line 2: target term contains the control character U+000A."
+        );
+        let entries =
+            GlossaryEntries::from_csv("Widget,Widget\n\"nut, \"\"hex\"\"\",Mutter").unwrap();
+        assert_eq!(entries.get("nut, \"hex\""), Some("Mutter"));
+
+        let cases = [
+            ("a\tb\nc", 2, "missing tab between source and target term"),
+            ("a\tb\n\tc", 2, "empty source term"),
+            (
+                "a\tb\tc",
+                1,
+                "target term contains the control character U+0009",
+            ),
+            (
+                "a\tb\u{80}c",
+                1,
+                "target term contains the control character U+0080",
+            ),
+        ];
+        for (tsv, line, reason) in cases.iter() {
+            match GlossaryEntries::from_tsv(tsv) {
+                Err(Error::InvalidGlossary {
+                    line: error_line,
+                    reason: error_reason,
+                }) => assert_eq!((error_line, error_reason.as_str()), (*line, *reason)),
+                result => panic!("unexpected result {:?} for {:?}", result, tsv),
+            }
+        }
+        assert!(matches!(
+            GlossaryEntries::from_csv("a,b\nc,d,e"),
+            Err(Error::InvalidGlossary { line: 2, .. })
+        ));
+        assert!(matches!(
+            GlossaryEntries::from_csv("a,\"b"),
+            Err(Error::InvalidGlossary { line: 1, .. })
+        ));
+
+        let mut entries = GlossaryEntries::new();
+        let long = "x".repeat(MAX_GLOSSARY_SIZE / 2);
+        entries.add("a", &long).unwrap();
+        assert!(matches!(
+            entries.add("b", &long),
+            Err(Error::InvalidGlossary { line: 2, .. })
+        ));
+        assert_eq!(entries.len(), 1);
+
+        let dictionary = GlossaryDictionary::from_entries(Language::En, Language::De, &entries);
+        assert_eq!(dictionary.entries.len(), MAX_GLOSSARY_SIZE / 2 + 2);
+    }
+
+    #[test]
+    fn glossary_entries_from_maps() {
+        let map: HashMap<String, String> = vec![("screw", "Schraube"), ("nut", "Mutter")]
+            .into_iter()
+            .map(|(s, t)| (s.to_string(), t.to_string()))
+            .collect();
+        let entries = GlossaryEntries::try_from(map).unwrap();
+        assert_eq!(entries.to_tsv(), "nut\tMutter\nscrew\tSchraube");
+        assert_eq!(
+            GlossaryEntries::from_tsv("\nscrew\tSchraube\nnut\tMutter").unwrap(),
+            GlossaryEntries::from_iter(vec![("screw", "Schraube"), ("nut", "Mutter")]).unwrap()
+        );
+        assert!(matches!(
+            GlossaryEntries::from_iter(vec![("nut", "Mutter"), ("nut", "Schraube")]),
+            Err(Error::InvalidGlossary { line: 2, .. })
+        ));
+
+        let other =
+            GlossaryEntries::from_iter(vec![("nut", "Schraubenmutter"), ("bolt", "Bolzen")]);
+        let other = other.unwrap();
+        let diff = entries.diff(&other);
+        assert_eq!(
+            diff.changed,
+            vec![(
+                "nut".to_string(),
+                "Mutter".to_string(),
+                "Schraubenmutter".to_string()
+            )]
+        );
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+        assert!(entries.diff(&entries).is_empty());
+
+        let mut merged = entries.clone();
+        merged.merge(&other).unwrap();
+        assert_eq!(
+            merged.to_tsv(),
+            "nut\tSchraubenmutter\nscrew\tSchraube\nbolt\tBolzen"
+        );
+        assert!(merged.diff(&other).added.is_empty());
+        // Merged entries stay unique and the size is tracked.
+        assert!(merged.add("bolt", "Schraube").is_err());
+        let long = "x".repeat(MAX_GLOSSARY_SIZE);
+        let mut large = GlossaryEntries::new();
+        large.add("bolt", &long[..MAX_GLOSSARY_SIZE - 20]).unwrap();
+        let nut = GlossaryEntries::from_iter(vec![("nut", "Schraubenmutter")]).unwrap();
+        assert!(matches!(
+            large.merge(&nut),
+            Err(Error::InvalidGlossary { line: 1, .. })
+        ));
+        assert_eq!(large.len(), 1);
+        // Only the final size counts, replacing the long target term makes room.
+        large.merge(&other).unwrap();
+        large.add("screw", &long[..MAX_GLOSSARY_SIZE - 40]).unwrap();
+        assert_eq!(large.len(), 3);
+    }
+
+    #[test]
+    fn glossary_option() {
+        let server = MockServer::deepl();
+        let options = TranslationOptions::new().glossary("def3a26b");
+        let texts = TranslatableTextList::new(Language::De, vec!["Widget"])
+            .with_source_language(Language::En);
+        server.client().translate(Some(options), texts).unwrap();
+        assert_eq!(
+            server.requests()[0].param_values("glossary_id"),
+            ["def3a26b"]
+        );
+    }
+}
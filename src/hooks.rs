@@ -0,0 +1,116 @@
+//! Middleware for the HTTP requests of the client.
+//!
+//! Register a [ClientHook] with [DeepL::with_hook](crate::DeepL::with_hook) to inspect or change
+//! every request before it is sent and to inspect every response, e. g. to add custom headers for
+//! a corporate proxy or to write an audit log. Hooks run for each attempt, so also for
+//! [retries](crate::DeepL::with_retry_policy), in the order in which they were registered.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::hooks::{ClientHook, Request, Response};
+//! use deepl_api::*;
+//! use std::sync::Arc;
+//!
+//! struct Tenant(&'static str);
+//!
+//! impl ClientHook for Tenant {
+//!     fn on_request(&self, request: &mut Request) -> Result<()> {
+//!         request
+//!             .headers_mut()
+//!             .insert("x-tenant", self.0.parse().unwrap());
+//!         Ok(())
+//!     }
+//!
+//!     fn on_response(&self, request: &Request, response: &Response) {
+//!         eprintln!("{} {} -> {}", self.0, request.url().path(), response.status());
+//!     }
+//! }
+//!
+//! let deepl = DeepL::new("key".to_string(), false).with_hook(Arc::new(Tenant("shop")));
+//! ```
+
+use crate::Result;
+
+pub use reqwest::blocking::{Request, Response};
+
+/// Callbacks around every HTTP request of the client.
+///
+/// The hooks never see the API key: it is added to the request after [on_request] ran, so that
+/// requests can be logged safely.
+///
+/// [on_request]: ClientHook::on_request
+pub trait ClientHook: Send + Sync {
+    /// Called before a request is sent. The hook may change the request, e. g. add headers or
+    /// query parameters. Returning an error aborts the request with that error.
+    fn on_request(&self, _request: &mut Request) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called when a response was received, before it is processed by the client.
+    fn on_response(&self, _request: &Request, _response: &Response) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::Error;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Audit {
+        log: Mutex<Vec<String>>,
+    }
+
+    impl ClientHook for Audit {
+        fn on_request(&self, request: &mut Request) -> Result<()> {
+            request
+                .headers_mut()
+                .insert("x-audit", "yes".parse().unwrap());
+            request
+                .url_mut()
+                .query_pairs_mut()
+                .append_pair("extra", "1");
+            Ok(())
+        }
+
+        fn on_response(&self, request: &Request, response: &Response) {
+            self.log.lock().unwrap().push(format!(
+                "{} {}",
+                request.url().path(),
+                response.status().as_u16()
+            ));
+        }
+    }
+
+    struct Deny;
+
+    impl ClientHook for Deny {
+        fn on_request(&self, _request: &mut Request) -> Result<()> {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied").into())
+        }
+    }
+
+    #[test]
+    fn client_hooks() {
+        let server = MockServer::deepl();
+        let audit = Arc::new(Audit::default());
+        let deepl = server.client().with_hook(audit.clone());
+
+        deepl.usage_information().unwrap();
+        deepl.source_languages().unwrap();
+        let request = &server.requests()[0];
+        assert_eq!(request.header("x-audit"), Some("yes"));
+        assert_eq!(request.path, "/v2/usage?extra=1");
+        assert_eq!(
+            *audit.log.lock().unwrap(),
+            vec!["/v2/usage 200", "/v2/languages 200"]
+        );
+
+        let deepl = server.client().with_hook(Arc::new(Deny)).with_hook(audit);
+        assert!(matches!(deepl.usage_information(), Err(Error::IO(_))));
+        assert_eq!(server.requests().len(), 2);
+    }
+}
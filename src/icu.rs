@@ -0,0 +1,509 @@
+//! Translation of [ICU MessageFormat](https://unicode-org.github.io/icu/userguide/format_parse/messages/)
+//! messages, see [DeepL::translate_icu].
+//!
+//! Only the literal text of a message is translated. Arguments like `{name}` or
+//! `{amount, number, currency}` and the `#` of plural branches are kept as placeholders, and the
+//! branches of `plural`, `selectordinal` and `select` arguments are translated one by one, so the
+//! structure of the message stays intact. The translated messages are checked to parse again and to
+//! contain all arguments of the source messages.
+
+use crate::xml;
+use crate::{DeepL, Error, Result, TagHandling, TranslatableTextList, TranslationOptions};
+
+/// A part of a message.
+#[derive(Debug, PartialEq)]
+enum Node {
+    /// Literal text, with the ICU quoting resolved.
+    Text(String),
+    /// The number of a plural branch.
+    Pound,
+    /// A simple argument, as written in the message.
+    Argument(String),
+    /// A `plural`, `selectordinal` or `select` argument. Each branch holds the message source up
+    /// to the opening brace of the branch (e. g. `{count, plural, one {`), and the message of the
+    /// branch. `end` is the source after the closing brace of the last branch. `plural` tells
+    /// whether `#` is the number in the branches.
+    Choice {
+        branches: Vec<(String, Vec<Node>)>,
+        end: String,
+        plural: bool,
+    },
+}
+
+impl DeepL {
+    /// Translate ICU messages, e. g. of an i18n catalog, see the [module documentation](crate::icu).
+    /// Returns the translated messages in the order of `text_list`.
+    ///
+    /// Fails with [InvalidMessage](Error::InvalidMessage) if a message does not parse, or if its
+    /// translation lost an argument. All texts are sent in as few requests as possible, with
+    /// [tag_handling](TranslationOptions::tag_handling) set to [Xml](TagHandling::Xml).
+    ///
+    /// ```rust,no_run
+    /// use deepl_api::*;
+    ///
+    /// let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap(), false);
+    /// let messages = TranslatableTextList::new(
+    ///     Language::De,
+    ///     ["{count, plural, one {You have # new message} other {You have # new messages}}"],
+    /// );
+    /// let translated = deepl.translate_icu(None, messages).unwrap();
+    /// assert_eq!(
+    ///     translated[0],
+    ///     "{count, plural, one {Sie haben # neue Nachricht} other {Sie haben # neue Nachrichten}}"
+    /// );
+    /// ```
+    pub fn translate_icu(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<String>> {
+        let messages = text_list
+            .texts
+            .iter()
+            .enumerate()
+            .map(|(index, message)| {
+                parse(message).map_err(|reason| Error::InvalidMessage { index, reason })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut units = Vec::new();
+        for message in &messages {
+            collect(message, &mut units);
+        }
+        let translations = match units.is_empty() {
+            true => Vec::new(),
+            false => {
+                let options = options.unwrap_or_default().tag_handling(TagHandling::Xml);
+                let units = TranslatableTextList {
+                    texts: units,
+                    ..text_list
+                };
+                self.translate(Some(options), units)?
+            }
+        };
+
+        let mut translations = translations.into_iter().map(|t| t.text);
+        let mut translated = Vec::with_capacity(messages.len());
+        for (index, message) in messages.iter().enumerate() {
+            let invalid = |reason: String| Error::InvalidMessage { index, reason };
+            let text = render(message, false, &mut translations).map_err(invalid)?;
+            parse(&text).map_err(|reason| invalid(format!("translation: {}", reason)))?;
+            translated.push(text);
+        }
+        Ok(translated)
+    }
+}
+
+/// Private helper that parses an ICU message.
+fn parse(message: &str) -> std::result::Result<Vec<Node>, String> {
+    let mut parser = Parser {
+        src: message,
+        pos: 0,
+    };
+    let nodes = parser.message(false)?;
+    match parser.peek() {
+        None => Ok(nodes),
+        Some(c) => Err(format!("unexpected '{}' at {}", c, parser.pos)),
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.next();
+        }
+    }
+
+    /// A word of an argument, like its name, type or a selector.
+    fn word(&mut self) -> &str {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | ','))
+        {
+            self.next();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn expect(&mut self, expected: char) -> std::result::Result<(), String> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!(
+                "expected '{}', found '{}' at {}",
+                expected,
+                c,
+                self.pos - 1
+            )),
+            None => Err(format!("expected '{}', found the end", expected)),
+        }
+    }
+
+    /// A message, up to the end or to the closing brace of the enclosing argument.
+    fn message(&mut self, in_plural: bool) -> std::result::Result<Vec<Node>, String> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '}' => break,
+                '{' => {
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+                    nodes.push(self.argument(in_plural)?);
+                }
+                '#' if in_plural => {
+                    self.next();
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(std::mem::take(&mut text)));
+                    }
+                    nodes.push(Node::Pound);
+                }
+                '\'' => {
+                    self.next();
+                    match self.peek() {
+                        Some('\'') => {
+                            self.next();
+                            text.push('\'');
+                        }
+                        Some('{') | Some('}') => self.quoted(&mut text)?,
+                        Some('#') if in_plural => self.quoted(&mut text)?,
+                        _ => text.push('\''),
+                    }
+                }
+                c => {
+                    self.next();
+                    text.push(c);
+                }
+            }
+        }
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        Ok(nodes)
+    }
+
+    /// Quoted literal text, after the opening apostrophe.
+    fn quoted(&mut self, text: &mut String) -> std::result::Result<(), String> {
+        loop {
+            match self.next() {
+                Some('\'') if self.peek() == Some('\'') => {
+                    self.next();
+                    text.push('\'');
+                }
+                Some('\'') => return Ok(()),
+                Some(c) => text.push(c),
+                None => return Err("unterminated quoted text".to_string()),
+            }
+        }
+    }
+
+    /// An argument, starting at its opening brace.
+    fn argument(&mut self, in_plural: bool) -> std::result::Result<Node, String> {
+        let start = self.pos;
+        self.expect('{')?;
+        self.skip_whitespace();
+        if self.word().is_empty() {
+            return Err(format!("missing argument name at {}", self.pos));
+        }
+        self.skip_whitespace();
+        if self.peek() == Some(',') {
+            self.next();
+            self.skip_whitespace();
+            let kind = self.word().to_string();
+            self.skip_whitespace();
+            if matches!(kind.as_str(), "plural" | "selectordinal" | "select") {
+                self.expect(',')?;
+                let plural = kind != "select" || in_plural;
+                return self.choice(start, plural);
+            }
+            if self.peek() == Some(',') {
+                // The style, which may contain nested braces.
+                let mut depth = 0;
+                while let Some(c) = self.peek() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' if depth == 0 => break,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    self.next();
+                }
+            }
+        }
+        self.expect('}')?;
+        Ok(Node::Argument(self.src[start..self.pos].to_string()))
+    }
+
+    /// The branches of a choice argument, after the comma that follows its type.
+    fn choice(&mut self, start: usize, in_plural: bool) -> std::result::Result<Node, String> {
+        let mut branches = Vec::new();
+        let mut prefix_start = start;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('}') => break,
+                None => return Err("unterminated argument".to_string()),
+                _ => {}
+            }
+            let selector = self.word().to_string();
+            if selector.starts_with("offset:") {
+                if selector == "offset:" {
+                    self.skip_whitespace();
+                    self.word();
+                }
+                continue;
+            }
+            if selector.is_empty() {
+                return Err(format!("missing selector at {}", self.pos));
+            }
+            self.skip_whitespace();
+            self.expect('{')?;
+            let prefix = self.src[prefix_start..self.pos].to_string();
+            let message = self.message(in_plural)?;
+            self.expect('}')?;
+            branches.push((prefix, message));
+            prefix_start = self.pos;
+        }
+        if !branches
+            .iter()
+            .any(|(prefix, _)| prefix.trim_end_matches('{').trim_end().ends_with("other"))
+        {
+            return Err("missing 'other' branch".to_string());
+        }
+        self.expect('}')?;
+        Ok(Node::Choice {
+            branches,
+            end: self.src[prefix_start..self.pos].to_string(),
+            plural: in_plural,
+        })
+    }
+}
+
+/// Whether `nodes` have text to translate.
+fn has_text(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Text(text) => !text.trim().is_empty(),
+        _ => false,
+    })
+}
+
+/// Private helper that appends the texts to translate of `nodes` to `units`: the message itself,
+/// as XML with `<x i="N"/>` for its arguments, followed by the texts of the branches.
+fn collect(nodes: &[Node], units: &mut Vec<String>) {
+    if has_text(nodes) {
+        let mut xml = String::new();
+        for (i, node) in nodes.iter().enumerate() {
+            match node {
+                Node::Text(text) => xml::escape_into(text, &mut xml),
+                _ => xml.push_str(&format!("<x i=\"{}\"/>", i)),
+            }
+        }
+        units.push(xml);
+    }
+    for node in nodes {
+        if let Node::Choice { branches, .. } = node {
+            for (_, message) in branches {
+                collect(message, units);
+            }
+        }
+    }
+}
+
+/// Private helper that turns `nodes` back into an ICU message, taking the translations in the
+/// order of [collect].
+fn render(
+    nodes: &[Node],
+    in_plural: bool,
+    translations: &mut impl Iterator<Item = String>,
+) -> std::result::Result<String, String> {
+    let translation = match has_text(nodes) {
+        true => Some(translations.next().ok_or("missing translation")?),
+        false => None,
+    };
+    let mut rendered = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        rendered.push(match node {
+            Node::Text(text) => escape(text, in_plural),
+            Node::Pound => "#".to_string(),
+            Node::Argument(argument) => argument.clone(),
+            Node::Choice {
+                branches,
+                end,
+                plural,
+            } => {
+                let mut choice = String::new();
+                for (prefix, message) in branches {
+                    choice.push_str(prefix);
+                    choice.push_str(&render(message, *plural, translations)?);
+                    choice.push('}');
+                }
+                choice.push_str(end);
+                choice
+            }
+        });
+    }
+    let translation = match translation {
+        Some(translation) => translation,
+        None => return Ok(rendered.concat()),
+    };
+
+    // Replace the placeholders of the translation by the rendered arguments.
+    let mut message = String::new();
+    let mut used = vec![false; nodes.len()];
+    let mut rest = translation.as_str();
+    while let Some(start) = rest.find("<x i=\"") {
+        message.push_str(&escape(&xml::unescape(&rest[..start]), in_plural));
+        let tag = &rest[start..];
+        let end = tag.find("/>").ok_or("unterminated placeholder")? + 2;
+        let id = tag[6..end - 2].trim().trim_end_matches('"');
+        match id.parse::<usize>().ok().filter(|&id| id < nodes.len()) {
+            Some(id) if !used[id] && !matches!(nodes[id], Node::Text(_)) => {
+                used[id] = true;
+                message.push_str(&rendered[id]);
+            }
+            _ => return Err(format!("unexpected placeholder {}", &tag[..end])),
+        }
+        rest = &tag[end..];
+    }
+    message.push_str(&escape(&xml::unescape(rest), in_plural));
+    let lost = nodes
+        .iter()
+        .zip(used)
+        .find(|(node, used)| !used && !matches!(node, Node::Text(_)));
+    match lost {
+        Some((node, _)) => Err(format!("the translation lost {}", describe(node))),
+        None => Ok(message),
+    }
+}
+
+/// The argument of `node` for error messages, like `{name}` or `{count, plural, …}`.
+fn describe(node: &Node) -> String {
+    match node {
+        Node::Text(text) => text.clone(),
+        Node::Pound => "#".to_string(),
+        Node::Argument(argument) => argument.clone(),
+        Node::Choice { branches, .. } => {
+            let prefix = branches.first().map_or("", |(prefix, _)| prefix.as_str());
+            let head: Vec<&str> = prefix.splitn(3, ',').take(2).collect();
+            format!("{}, …}}", head.join(","))
+        }
+    }
+}
+
+/// Quote the characters of `text` that have a meaning in ICU messages.
+fn escape(text: &str, in_plural: bool) -> String {
+    let special = |c: char| matches!(c, '{' | '}' | '\'') || (in_plural && c == '#');
+    let mut escaped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // An apostrophe before a special character or another argument would start quoting.
+            '\'' if chars.peek().is_none_or(|&next| special(next)) => escaped.push_str("''"),
+            c if c != '\'' && special(c) => {
+                escaped.push('\'');
+                escaped.push(c);
+                escaped.push('\'');
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    const MESSAGE: &str = "Hello {name}! {count, plural, offset:1 =0 {No messages} one {# message from {sender}} \
+         other {# messages, '{'{total, number}'}' & more}} {gender, select, female {She} other {They}}.";
+
+    #[test]
+    fn parse_messages() {
+        let nodes = parse(MESSAGE).unwrap();
+        let mut units = Vec::new();
+        collect(&nodes, &mut units);
+        assert_eq!(
+            units,
+            [
+                "Hello <x i=\"1\"/>! <x i=\"3\"/> <x i=\"5\"/>.",
+                "No messages",
+                "<x i=\"0\"/> message from <x i=\"2\"/>",
+                "<x i=\"0\"/> messages, {<x i=\"2\"/>} &amp; more",
+                "She",
+                "They",
+            ]
+        );
+        // Rendering the untranslated units yields an equivalent message.
+        let rendered = render(&nodes, false, &mut units.clone().into_iter()).unwrap();
+        assert_eq!(rendered, MESSAGE);
+        assert_eq!(parse(&rendered).unwrap(), nodes);
+
+        assert_eq!(
+            parse("It''s '{'quoted'}' and '#' isn't").unwrap(),
+            [Node::Text("It's {quoted} and '#' isn't".to_string())]
+        );
+        assert_eq!(escape("It's {x} #1'", true), "It's '{'x'}' '#'1''");
+        for invalid in [
+            "{",
+            "{count, plural, one {#}}",
+            "Hello}",
+            "{x, select, other {'{}}",
+        ] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn translate_icu() {
+        // Translates "message" and drops the placeholders from "lost".
+        let server = MockServer::translating(|text| match text.starts_with("lost") {
+            true => "verloren".to_string(),
+            false => text.replace("message", "Nachricht"),
+        });
+        let deepl = server.client();
+        let messages = TranslatableTextList::new("DE", [MESSAGE, "{name}"]);
+        let translated = deepl.translate_icu(None, messages).unwrap();
+        assert_eq!(
+            translated,
+            [MESSAGE.replace("message", "Nachricht").as_str(), "{name}"]
+        );
+        assert_eq!(server.requests()[0].param_values("text").len(), 6);
+
+        let messages = TranslatableTextList::new("DE", ["{name}", "lost {name}"]);
+        match deepl.translate_icu(None, messages) {
+            Err(Error::InvalidMessage { index: 1, reason }) => {
+                assert_eq!(reason, "the translation lost {name}")
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        let messages = TranslatableTextList::new("DE", ["lost {n, select, a {A} other {B}}"]);
+        match deepl.translate_icu(None, messages) {
+            Err(Error::InvalidMessage { reason, .. }) => {
+                assert_eq!(reason, "the translation lost {n, select, …}")
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        let messages = TranslatableTextList::new("DE", ["{name"]);
+        assert!(matches!(
+            deepl.translate_icu(None, messages),
+            Err(Error::InvalidMessage { index: 0, .. })
+        ));
+        assert_eq!(server.requests().len(), 3);
+    }
+}
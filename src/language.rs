@@ -0,0 +1,214 @@
+//! Typed language codes.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+macro_rules! languages {
+    ($($variant:ident => $code:literal, $name:literal;)*) => {
+        /// A language code as used by DeepL for source and target languages, e. g. `EN-US`.
+        ///
+        /// Codes that are not (yet) known to this crate can be used via [Language::Other].
+        /// Parsing is case-insensitive and never fails; unknown codes end up in [Language::Other].
+        ///
+        /// ```rust
+        /// use deepl_api::Language;
+        ///
+        /// let language: Language = "en-us".parse().unwrap();
+        /// assert_eq!(language, Language::EnUs);
+        /// assert_eq!(language.to_string(), "EN-US");
+        /// assert_eq!(Language::from("XX"), Language::Other("XX".to_string()));
+        /// ```
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum Language {
+            $(
+                #[doc = $name]
+                $variant,
+            )*
+            /// Any other language code, passed to DeepL as is.
+            Other(String),
+        }
+
+        impl Language {
+            /// The language code as expected by DeepL.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Language::$variant => $code,)*
+                    Language::Other(code) => code,
+                }
+            }
+        }
+
+        impl FromStr for Language {
+            type Err = Infallible;
+
+            fn from_str(code: &str) -> Result<Language, Infallible> {
+                let code = code.trim();
+                $(
+                    if code.eq_ignore_ascii_case($code) {
+                        return Ok(Language::$variant);
+                    }
+                )*
+                Ok(Language::Other(code.to_string()))
+            }
+        }
+    };
+}
+
+languages! {
+    Ar => "AR", "Arabic";
+    Bg => "BG", "Bulgarian";
+    Cs => "CS", "Czech";
+    Da => "DA", "Danish";
+    De => "DE", "German";
+    El => "EL", "Greek";
+    En => "EN", "English";
+    EnGb => "EN-GB", "English (British)";
+    EnUs => "EN-US", "English (American)";
+    Es => "ES", "Spanish";
+    Es419 => "ES-419", "Spanish (Latin American)";
+    Et => "ET", "Estonian";
+    Fi => "FI", "Finnish";
+    Fr => "FR", "French";
+    Hu => "HU", "Hungarian";
+    Id => "ID", "Indonesian";
+    It => "IT", "Italian";
+    Ja => "JA", "Japanese";
+    Ko => "KO", "Korean";
+    Lt => "LT", "Lithuanian";
+    Lv => "LV", "Latvian";
+    Nb => "NB", "Norwegian (Bokmål)";
+    Nl => "NL", "Dutch";
+    Pl => "PL", "Polish";
+    Pt => "PT", "Portuguese";
+    PtBr => "PT-BR", "Portuguese (Brazilian)";
+    PtPt => "PT-PT", "Portuguese (European)";
+    Ro => "RO", "Romanian";
+    Ru => "RU", "Russian";
+    Sk => "SK", "Slovak";
+    Sl => "SL", "Slovenian";
+    Sv => "SV", "Swedish";
+    Tr => "TR", "Turkish";
+    Uk => "UK", "Ukrainian";
+    Zh => "ZH", "Chinese";
+    ZhHans => "ZH-HANS", "Chinese (simplified)";
+    ZhHant => "ZH-HANT", "Chinese (traditional)";
+}
+
+/// The concrete variants that replace the deprecated generic target languages `EN` and `PT`, see
+/// [Language::normalize_target].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetVariants {
+    /// Replacement for `EN`, [Language::EnUs] by default.
+    pub english: Language,
+    /// Replacement for `PT`, [Language::PtBr] by default.
+    pub portuguese: Language,
+}
+
+impl Default for TargetVariants {
+    fn default() -> TargetVariants {
+        TargetVariants {
+            english: Language::EnUs,
+            portuguese: Language::PtBr,
+        }
+    }
+}
+
+impl Language {
+    /// Replace a deprecated generic target language with the concrete variant from `variants`,
+    /// e. g. `EN` with `EN-US`. Unknown codes are converted to upper case, all other languages
+    /// are returned unchanged.
+    ///
+    /// ```rust
+    /// use deepl_api::{Language, TargetVariants};
+    ///
+    /// let variants = TargetVariants {
+    ///     english: Language::EnGb,
+    ///     ..TargetVariants::default()
+    /// };
+    /// assert_eq!(Language::from("en").normalize_target(&variants), Language::EnGb);
+    /// assert_eq!(Language::Pt.normalize_target(&variants), Language::PtBr);
+    /// assert_eq!(Language::De.normalize_target(&variants), Language::De);
+    /// ```
+    pub fn normalize_target(&self, variants: &TargetVariants) -> Language {
+        match self {
+            Language::En => variants.english.clone(),
+            Language::Pt => variants.portuguese.clone(),
+            Language::Other(code) => Language::Other(code.to_uppercase()),
+            language => language.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for Language {
+    fn from(code: &str) -> Language {
+        match code.parse() {
+            Ok(language) => language,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl From<String> for Language {
+    fn from(code: String) -> Language {
+        Language::from(code.as_str())
+    }
+}
+
+impl From<&String> for Language {
+    fn from(code: &String) -> Language {
+        Language::from(code.as_str())
+    }
+}
+
+impl From<Language> for String {
+    fn from(language: Language) -> String {
+        match language {
+            Language::Other(code) => code,
+            language => language.as_str().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language() {
+        assert_eq!("DE".parse::<Language>().unwrap(), Language::De);
+        assert_eq!(Language::from(" zh-hant "), Language::ZhHant);
+        assert_eq!(Language::from("es-419").to_string(), "ES-419");
+        assert_eq!(
+            Language::from("tlh".to_string()),
+            Language::Other("tlh".to_string())
+        );
+        assert_eq!(String::from(Language::PtBr), "PT-BR");
+        assert_eq!(String::from(Language::Other("tlh".to_string())), "tlh");
+    }
+
+    #[test]
+    fn normalize_target() {
+        let variants = TargetVariants {
+            english: Language::EnGb,
+            portuguese: Language::PtPt,
+        };
+        assert_eq!(Language::En.normalize_target(&variants), Language::EnGb);
+        assert_eq!(
+            Language::from("pt").normalize_target(&variants),
+            Language::PtPt
+        );
+        assert_eq!(Language::EnUs.normalize_target(&variants), Language::EnUs);
+        assert_eq!(
+            Language::from("tlh").normalize_target(&variants),
+            Language::Other("TLH".to_string())
+        );
+    }
+}
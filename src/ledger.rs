@@ -0,0 +1,348 @@
+//! Local bookkeeping of translated characters, for capacity planning.
+//!
+//! A [UsageLedger] records how many characters were sent for translation and when. Attach it to a
+//! client with [DeepL::with_usage_ledger](crate::DeepL::with_usage_ledger) to record every
+//! translation automatically. Based on the records, the ledger can summarize the daily consumption
+//! and [forecast](UsageLedger::forecast_exhaustion) when the account's character quota will run out.
+//!
+//! If DeepL reports the characters it billed, the ledger keeps them next to the characters computed by
+//! the client, and [reconcile](UsageLedger::reconcile) points out the translations where both differ.
+
+use crate::store::Store;
+use crate::{Error, Result, UsageInformation};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NAMESPACE: &str = "ledger";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// One entry of the [UsageLedger].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    /// Number of characters sent for translation, as computed by the client.
+    pub characters: u64,
+    /// Number of characters billed by DeepL, if it was reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billed_characters: Option<u64>,
+}
+
+impl UsageRecord {
+    /// Billed minus computed characters, if the billed characters are known.
+    pub fn discrepancy(&self) -> Option<i64> {
+        self.billed_characters
+            .map(|billed| billed as i64 - self.characters as i64)
+    }
+}
+
+/// Characters consumed on one calendar day (UTC).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DailyUsage {
+    /// The day in `YYYY-MM-DD` format.
+    pub date: String,
+    /// Number of characters sent for translation on that day.
+    pub characters: u64,
+}
+
+/// Result of [UsageLedger::forecast_exhaustion].
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsageForecast {
+    /// Average consumption in characters per day during the observed window.
+    pub characters_per_day: f64,
+    /// Characters left in the current billing period.
+    pub remaining_characters: u64,
+    /// Estimated point in time when the quota will be used up, or `None` if nothing was consumed
+    /// during the observed window.
+    pub exhaustion: Option<SystemTime>,
+}
+
+/// Result of [UsageLedger::reconcile].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reconciliation {
+    /// Characters computed by the client, summed over all records with billing information.
+    pub computed_characters: u64,
+    /// Characters billed by DeepL, summed over the same records.
+    pub billed_characters: u64,
+    /// Records whose billed characters differ from the computed ones by more than the tolerance.
+    pub discrepancies: Vec<UsageRecord>,
+}
+
+/// Records translated characters in a [Store].
+pub struct UsageLedger {
+    store: Arc<dyn Store>,
+    sequence: AtomicU64,
+}
+
+impl UsageLedger {
+    /// Create a ledger that keeps its records in the namespace `ledger` of `store`.
+    pub fn new(store: Arc<dyn Store>) -> UsageLedger {
+        UsageLedger {
+            store,
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    /// Record that `characters` were sent for translation just now.
+    pub fn record(&self, characters: u64) -> Result<()> {
+        self.record_at(SystemTime::now(), characters)
+    }
+
+    /// Record that `characters` were sent for translation at `time`.
+    pub fn record_at(&self, time: SystemTime, characters: u64) -> Result<()> {
+        self.insert(UsageRecord {
+            timestamp: unix_seconds(time),
+            characters,
+            billed_characters: None,
+        })
+    }
+
+    /// Record that `characters` were sent for translation just now, of which DeepL billed
+    /// `billed_characters`.
+    pub fn record_billed(&self, characters: u64, billed_characters: u64) -> Result<()> {
+        self.record_billed_at(SystemTime::now(), characters, billed_characters)
+    }
+
+    /// Like [record_billed](UsageLedger::record_billed), but at `time`.
+    pub fn record_billed_at(
+        &self,
+        time: SystemTime,
+        characters: u64,
+        billed_characters: u64,
+    ) -> Result<()> {
+        self.insert(UsageRecord {
+            timestamp: unix_seconds(time),
+            characters,
+            billed_characters: Some(billed_characters),
+        })
+    }
+
+    fn insert(&self, record: UsageRecord) -> Result<()> {
+        // Zero-padded keys keep the records ordered by time; the sequence number and process id
+        // keep keys unique if several records share a timestamp.
+        let key = format!(
+            "{:020}-{:010}-{:020}",
+            record.timestamp,
+            std::process::id(),
+            self.sequence.fetch_add(1, Ordering::SeqCst)
+        );
+        let value = serde_json::to_vec(&record).map_err(|e| Error::StorageError(e.to_string()))?;
+        self.store.put(NAMESPACE, &key, &value)
+    }
+
+    /// All records, ordered by time.
+    pub fn records(&self) -> Result<Vec<UsageRecord>> {
+        self.store
+            .scan(NAMESPACE, "")?
+            .into_iter()
+            .map(|(_, value)| {
+                serde_json::from_slice(&value).map_err(|e| Error::StorageError(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Characters consumed per calendar day (UTC), ordered by date. Days without consumption
+    /// are omitted.
+    pub fn daily_usage(&self) -> Result<Vec<DailyUsage>> {
+        let mut days = BTreeMap::new();
+        for record in self.records()? {
+            *days.entry(record.timestamp / SECONDS_PER_DAY).or_insert(0) += record.characters;
+        }
+        Ok(days
+            .into_iter()
+            .map(|(day, characters)| DailyUsage {
+                date: format_date(day),
+                characters,
+            })
+            .collect())
+    }
+
+    /// Compare the computed with the billed characters of all records that carry billing information.
+    ///
+    /// A record counts as a discrepancy if the difference exceeds `tolerance`, given as a fraction
+    /// of the computed characters (e. g. `0.05` for 5%).
+    pub fn reconcile(&self, tolerance: f64) -> Result<Reconciliation> {
+        let mut reconciliation = Reconciliation {
+            computed_characters: 0,
+            billed_characters: 0,
+            discrepancies: Vec::new(),
+        };
+        for record in self.records()? {
+            let discrepancy = match record.discrepancy() {
+                Some(discrepancy) => discrepancy,
+                None => continue,
+            };
+            reconciliation.computed_characters += record.characters;
+            reconciliation.billed_characters += record.billed_characters.unwrap_or(0);
+            if discrepancy.unsigned_abs() as f64 > tolerance * record.characters as f64 {
+                reconciliation.discrepancies.push(record);
+            }
+        }
+        Ok(reconciliation)
+    }
+
+    /// Estimate when the character quota from `usage` will be exhausted, assuming the consumption
+    /// continues at the average rate of the last `window`.
+    pub fn forecast_exhaustion(
+        &self,
+        usage: &UsageInformation,
+        window: Duration,
+    ) -> Result<UsageForecast> {
+        self.forecast_exhaustion_at(SystemTime::now(), usage, window)
+    }
+
+    /// Like [forecast_exhaustion](UsageLedger::forecast_exhaustion), but relative to `now`.
+    pub fn forecast_exhaustion_at(
+        &self,
+        now: SystemTime,
+        usage: &UsageInformation,
+        window: Duration,
+    ) -> Result<UsageForecast> {
+        let end = unix_seconds(now);
+        let start = end.saturating_sub(window.as_secs());
+        let consumed: u64 = self
+            .records()?
+            .iter()
+            .filter(|record| record.timestamp > start && record.timestamp <= end)
+            .map(|record| record.characters)
+            .sum();
+
+        let remaining_characters = usage.character_limit.saturating_sub(usage.character_count);
+        let characters_per_second = match window.as_secs_f64() {
+            seconds if seconds > 0.0 => consumed as f64 / seconds,
+            _ => 0.0,
+        };
+        let exhaustion = match characters_per_second > 0.0 {
+            true => Some(
+                now + Duration::from_secs_f64(remaining_characters as f64 / characters_per_second),
+            ),
+            false => None,
+        };
+
+        Ok(UsageForecast {
+            characters_per_day: characters_per_second * SECONDS_PER_DAY as f64,
+            remaining_characters,
+            exhaustion,
+        })
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Formats days since the unix epoch as `YYYY-MM-DD`.
+fn format_date(days: u64) -> String {
+    // Converts to the proleptic Gregorian calendar, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+
+    fn day(days: u64, hours: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(days * SECONDS_PER_DAY + hours * 60 * 60)
+    }
+
+    #[test]
+    fn daily_usage() {
+        let ledger = UsageLedger::new(Arc::new(MemoryStore::new()));
+        // 2021-01-28 is day 18655 since the epoch.
+        ledger.record_at(day(18655, 8), 100).unwrap();
+        ledger.record_at(day(18655, 8), 50).unwrap();
+        ledger.record_at(day(18656, 23), 10).unwrap();
+        ledger.record_at(day(18658, 0), 5).unwrap();
+
+        assert_eq!(ledger.records().unwrap().len(), 4);
+        assert_eq!(
+            ledger.daily_usage().unwrap(),
+            vec![
+                DailyUsage {
+                    date: "2021-01-28".to_string(),
+                    characters: 150
+                },
+                DailyUsage {
+                    date: "2021-01-29".to_string(),
+                    characters: 10
+                },
+                DailyUsage {
+                    date: "2021-01-31".to_string(),
+                    characters: 5
+                },
+            ]
+        );
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(11016), "2000-02-29");
+    }
+
+    #[test]
+    fn forecast_exhaustion() {
+        let ledger = UsageLedger::new(Arc::new(MemoryStore::new()));
+        // Outside of the window.
+        ledger.record_at(day(0, 0), 1_000_000).unwrap();
+        for i in 1..=10 {
+            ledger.record_at(day(i, 12), 1000).unwrap();
+        }
+
+        let usage = UsageInformation {
+            character_limit: 500_000,
+            character_count: 490_000,
+            ..UsageInformation::default()
+        };
+        let now = day(11, 0);
+        let forecast = ledger
+            .forecast_exhaustion_at(now, &usage, Duration::from_secs(10 * SECONDS_PER_DAY))
+            .unwrap();
+        assert_eq!(forecast.characters_per_day, 1000.0);
+        assert_eq!(forecast.remaining_characters, 10_000);
+        assert_eq!(forecast.exhaustion, Some(day(21, 0)));
+
+        // Nothing consumed recently.
+        let forecast = ledger
+            .forecast_exhaustion_at(day(100, 0), &usage, Duration::from_secs(SECONDS_PER_DAY))
+            .unwrap();
+        assert_eq!(forecast.characters_per_day, 0.0);
+        assert_eq!(forecast.exhaustion, None);
+    }
+
+    #[test]
+    fn reconcile() {
+        let ledger = UsageLedger::new(Arc::new(MemoryStore::new()));
+        ledger.record_at(day(1, 0), 1000).unwrap();
+        ledger.record_billed_at(day(1, 1), 1000, 1000).unwrap();
+        ledger.record_billed_at(day(1, 2), 100, 104).unwrap();
+        ledger.record_billed_at(day(1, 3), 100, 120).unwrap();
+        ledger.record_billed_at(day(1, 4), 0, 1).unwrap();
+
+        let reconciliation = ledger.reconcile(0.05).unwrap();
+        assert_eq!(reconciliation.computed_characters, 1200);
+        assert_eq!(reconciliation.billed_characters, 1225);
+        assert_eq!(
+            reconciliation
+                .discrepancies
+                .iter()
+                .map(|record| record.discrepancy())
+                .collect::<Vec<_>>(),
+            vec![Some(20), Some(1)]
+        );
+
+        assert_eq!(ledger.records().unwrap()[0].discrepancy(), None);
+    }
+}
@@ -0,0 +1,636 @@
+//! Translation of Markdown documents, see [DeepL::translate_markdown].
+//!
+//! The document is split into blocks. Front matter, code blocks, HTML comments, link reference
+//! definitions, thematic breaks and table delimiter rows are kept as they are. The prose of the
+//! other blocks is translated without the block markers of headings, quotes and list items. Inline
+//! code, the URLs of links and images, autolinks, bare URLs and inline HTML are replaced by XML
+//! placeholder tags that DeepL leaves untouched, and restored after the translation. The texts of
+//! links and images are translated.
+//!
+//! Hard-wrapped paragraphs are joined into one line, so DeepL sees complete sentences. Emphasis
+//! markers are translated as part of the text.
+
+use crate::xml;
+use crate::{
+    DeepL, Error, Language, Result, TagHandling, TranslatableTextList, TranslationOptions,
+};
+
+/// A part of a Markdown document.
+#[derive(Debug, PartialEq)]
+enum Part {
+    /// Markdown that is kept as it is.
+    Verbatim(String),
+    /// Prose to translate.
+    Prose(Prose),
+}
+
+/// Prose as XML, with placeholders for the [protected](Protected) Markdown.
+#[derive(Debug, Default, PartialEq)]
+struct Prose {
+    xml: String,
+    protected: Vec<Protected>,
+}
+
+/// Markdown replaced by a placeholder in [Prose].
+#[derive(Debug, PartialEq)]
+enum Protected {
+    /// Replaced by `<x i="N"/>`.
+    Span(String),
+    /// A link or image, replaced by `<l i="N">text</l>` so that its text is translated.
+    Link { open: String, close: String },
+}
+
+impl DeepL {
+    /// Translate the Markdown document `markdown` into `target_language` and return the translated
+    /// Markdown, see the [module documentation](crate::markdown) for what is translated. The
+    /// source language is detected if `source_language` is `None`.
+    ///
+    /// The prose of all blocks is sent in as few requests as possible, with
+    /// [tag_handling](TranslationOptions::tag_handling) set to [Xml](TagHandling::Xml).
+    ///
+    /// ```rust,no_run
+    /// use deepl_api::*;
+    ///
+    /// let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap(), false);
+    /// let markdown = "# Hello\n\nRun `cargo build` and see [the docs](https://docs.rs).\n";
+    /// let translated = deepl
+    ///     .translate_markdown(markdown, None, Language::De, None)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     translated,
+    ///     "# Hallo\n\nFühren Sie `cargo build` aus und lesen Sie [die Doku](https://docs.rs).\n"
+    /// );
+    /// ```
+    pub fn translate_markdown(
+        &self,
+        markdown: &str,
+        source_language: Option<Language>,
+        target_language: impl Into<Language>,
+        options: Option<TranslationOptions>,
+    ) -> Result<String> {
+        let parts = parse(markdown);
+        let texts: Vec<&str> = parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Prose(prose) => Some(prose.xml.as_str()),
+                Part::Verbatim(_) => None,
+            })
+            .collect();
+        if texts.is_empty() {
+            return Ok(markdown.to_string());
+        }
+        let options = options.unwrap_or_default().tag_handling(TagHandling::Xml);
+        let mut text_list = TranslatableTextList::new(target_language, texts);
+        if let Some(source_language) = source_language {
+            text_list = text_list.with_source_language(source_language);
+        }
+        let mut translations = self.translate(Some(options), text_list)?.into_iter();
+
+        let mut translated = String::with_capacity(markdown.len());
+        for part in &parts {
+            match part {
+                Part::Verbatim(markdown) => translated.push_str(markdown),
+                Part::Prose(prose) => {
+                    let translation = translations.next().ok_or(Error::DeserializationError)?;
+                    translated.push_str(&restore(&translation.text, &prose.protected));
+                }
+            }
+        }
+        Ok(translated)
+    }
+}
+
+/// Collects the parts of a document, merging consecutive verbatim Markdown.
+#[derive(Default)]
+struct Parts(Vec<Part>);
+
+impl Parts {
+    fn verbatim(&mut self, markdown: &str) {
+        if markdown.is_empty() {
+            return;
+        }
+        match self.0.last_mut() {
+            Some(Part::Verbatim(verbatim)) => verbatim.push_str(markdown),
+            _ => self.0.push(Part::Verbatim(markdown.to_string())),
+        }
+    }
+
+    fn prose(&mut self, text: &str, table: bool) {
+        let mut prose = Prose::default();
+        prose.convert(text, table);
+        self.0.push(Part::Prose(prose));
+    }
+}
+
+/// Private helper that splits `markdown` into verbatim and prose parts.
+fn parse(markdown: &str) -> Vec<Part> {
+    let lines: Vec<&str> = markdown.split_inclusive('\n').collect();
+    let mut parts = Parts::default();
+    let mut i = front_matter(&lines);
+    parts.verbatim(&lines[..i].concat());
+    let (mut after_blank, mut in_code, mut in_list) = (true, false, false);
+    while i < lines.len() {
+        let content = lines[i].trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim_start();
+        let indent = content.len() - trimmed.len();
+        if trimmed.is_empty() {
+            parts.verbatim(lines[i]);
+            after_blank = true;
+            i += 1;
+            continue;
+        }
+
+        let prefix = block_prefix(content);
+        if is_list_item(&content[..prefix]) {
+            in_list = true;
+        } else if indent == 0 && after_blank {
+            in_list = false;
+        }
+
+        // Indented code blocks, but not the indented paragraphs of list items.
+        in_code = indent >= 4 && (in_code || (after_blank && !in_list));
+        let end = if in_code {
+            i + 1
+        } else if let Some(end) = verbatim_block(&lines, i) {
+            end
+        } else {
+            i
+        };
+        after_blank = false;
+        if end > i {
+            parts.verbatim(&lines[i..end].concat());
+            i = end;
+            continue;
+        }
+
+        parts.verbatim(&content[..prefix]);
+
+        // Table rows stay on their line, paragraphs are joined with their continuation lines.
+        let table = trimmed.starts_with('|');
+        let mut text = content[prefix..].to_string();
+        let mut last = i;
+        while !table && last + 1 < lines.len() && !hard_break(&text) {
+            let next = lines[last + 1].trim_end_matches(['\n', '\r']);
+            if !is_continuation(&lines, last + 1) {
+                break;
+            }
+            text = format!("{} {}", text.trim_end(), next.trim());
+            last += 1;
+        }
+        let line = lines[last];
+        let ending = &line[line.trim_end_matches(['\n', '\r']).len()..];
+        let body = text.trim_end();
+        let body = body.strip_suffix('\\').unwrap_or(body);
+        if body.is_empty() {
+            parts.verbatim(&text);
+        } else {
+            parts.prose(body, table);
+            parts.verbatim(&text[body.len()..]);
+        }
+        parts.verbatim(ending);
+        i = last + 1;
+    }
+    parts.0
+}
+
+/// The number of lines of YAML (`---`) or TOML (`+++`) front matter at the start of `lines`.
+fn front_matter(lines: &[&str]) -> usize {
+    let fence = match lines.first().map(|line| line.trim_end()) {
+        Some(fence @ "---") | Some(fence @ "+++") => fence,
+        _ => return 0,
+    };
+    lines
+        .iter()
+        .skip(1)
+        .position(|line| line.trim_end() == fence)
+        .map_or(0, |position| position + 2)
+}
+
+/// If a block that is kept verbatim starts at `lines[start]`, the index of the line after it.
+fn verbatim_block(lines: &[&str], start: usize) -> Option<usize> {
+    let content = lines[start].trim_end_matches(['\n', '\r']);
+    let trimmed = content.trim_start();
+    if content.len() - trimmed.len() < 4 {
+        if let Some(fence) = code_fence(trimmed) {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| {
+                    let line = line.trim();
+                    line.starts_with(fence)
+                        && line
+                            .trim_start_matches(fence.as_bytes()[0] as char)
+                            .is_empty()
+                })
+                .map_or(lines.len(), |position| start + position + 2);
+            return Some(end);
+        }
+    }
+    if trimmed.starts_with("<!--") {
+        let end = lines[start..]
+            .iter()
+            .position(|line| line.contains("-->"))
+            .map_or(lines.len(), |position| start + position + 1);
+        return Some(end);
+    }
+    let single_line = is_thematic_break(trimmed)
+        || is_setext_underline(trimmed)
+        || is_delimiter_row(trimmed)
+        || is_reference_definition(trimmed);
+    match single_line {
+        true => Some(start + 1),
+        false => None,
+    }
+}
+
+/// The opening fence of a fenced code block, e. g. ```` ``` ```` or `~~~~`.
+fn code_fence(line: &str) -> Option<&str> {
+    ['`', '~'].iter().find_map(|&c| {
+        let length = line.len() - line.trim_start_matches(c).len();
+        match length >= 3 {
+            true => Some(&line[..length]),
+            false => None,
+        }
+    })
+}
+
+fn is_thematic_break(line: &str) -> bool {
+    ['-', '*', '_'].iter().any(|&c| {
+        line.chars().filter(|&l| l == c).count() >= 3
+            && line.chars().all(|l| l == c || l == ' ' || l == '\t')
+    })
+}
+
+fn is_setext_underline(line: &str) -> bool {
+    line.trim_end().chars().all(|c| c == '=')
+}
+
+/// Delimiter rows of tables, like `| --- | :-: |`.
+fn is_delimiter_row(line: &str) -> bool {
+    line.contains('-')
+        && line.contains('|')
+        && line
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+/// Link reference definitions, like `[docs]: https://docs.rs "Docs"`.
+fn is_reference_definition(line: &str) -> bool {
+    match (line.find("]:"), line.find("](")) {
+        (Some(definition), link) => {
+            line.starts_with('[')
+                && !line.starts_with("[^")
+                && link.is_none_or(|link| definition < link)
+        }
+        (None, _) => false,
+    }
+}
+
+/// Whether the line after `lines[index - 1]` continues its paragraph.
+fn is_continuation(lines: &[&str], index: usize) -> bool {
+    let content = lines[index].trim_end_matches(['\n', '\r']);
+    let trimmed = content.trim_start();
+    !trimmed.is_empty()
+        && !trimmed.starts_with('|')
+        && block_prefix(trimmed) == 0
+        && verbatim_block(lines, index).is_none()
+}
+
+/// Lines ending with two spaces or a backslash end with a line break.
+fn hard_break(text: &str) -> bool {
+    text.ends_with("  ") || text.ends_with('\\')
+}
+
+fn is_list_item(prefix: &str) -> bool {
+    let marker = prefix.trim_start_matches([' ', '\t', '>']);
+    marker.starts_with(['-', '*', '+']) || marker.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// The length of the block markers at the start of `line`: indentation, quote markers, list item
+/// markers, task list boxes and heading markers, including the spaces after them.
+fn block_prefix(line: &str) -> usize {
+    let mut i = 0;
+    let mut list_item = false;
+    loop {
+        i = line.len() - line[i..].trim_start_matches([' ', '\t']).len();
+        let rest = &line[i..];
+        if rest.starts_with('>') {
+            i += 1;
+            continue;
+        }
+        if rest.starts_with("- ") || rest.starts_with("* ") || rest.starts_with("+ ") {
+            i += 2;
+            list_item = true;
+            continue;
+        }
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if (1..=9).contains(&digits)
+            && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") "))
+        {
+            i += digits + 2;
+            list_item = true;
+            continue;
+        }
+        if list_item
+            && (rest.starts_with("[ ] ") || rest.starts_with("[x] ") || rest.starts_with("[X] "))
+        {
+            i += 4;
+            list_item = false;
+            continue;
+        }
+        let hashes = rest.len() - rest.trim_start_matches('#').len();
+        if (1..=6).contains(&hashes) && (rest.len() == hashes || rest[hashes..].starts_with(' ')) {
+            i += hashes;
+            i = line.len() - line[i..].trim_start().len();
+        }
+        return i;
+    }
+}
+
+impl Prose {
+    /// Append `text` as XML, replacing the Markdown that must not be translated by placeholders.
+    /// In table rows, the cell separators are protected too.
+    fn convert(&mut self, text: &str, table: bool) {
+        let mut i = 0;
+        while let Some(c) = text[i..].chars().next() {
+            let rest = &text[i..];
+            let protected = match c {
+                '`' => code_span(rest),
+                '<' => inline_html(rest),
+                'h' if rest.starts_with("http://") || rest.starts_with("https://") => {
+                    bare_url(rest, &text[..i])
+                }
+                '|' if table => Some(1),
+                '\\' if table && rest.starts_with("\\|") => Some(2),
+                '[' | '!' => {
+                    if let Some((open, inner, close)) = link(rest) {
+                        let id = self.protected.len();
+                        self.protected.push(Protected::Link {
+                            open: rest[..open].to_string(),
+                            close: rest[inner..close].to_string(),
+                        });
+                        self.xml.push_str(&format!("<l i=\"{}\">", id));
+                        self.convert(&rest[open..inner], table);
+                        self.xml.push_str("</l>");
+                        i += close;
+                        continue;
+                    }
+                    None
+                }
+                _ => None,
+            };
+            match protected {
+                Some(length) => {
+                    self.xml
+                        .push_str(&format!("<x i=\"{}\"/>", self.protected.len()));
+                    self.protected
+                        .push(Protected::Span(rest[..length].to_string()));
+                    i += length;
+                }
+                None => {
+                    xml::escape_into(&rest[..c.len_utf8()], &mut self.xml);
+                    i += c.len_utf8();
+                }
+            }
+        }
+    }
+}
+
+/// The length of the code span at the start of `text`, closed by a backtick run of the same length.
+fn code_span(text: &str) -> Option<usize> {
+    let run = text.len() - text.trim_start_matches('`').len();
+    let mut i = run;
+    while let Some(start) = text[i..].find('`') {
+        let start = i + start;
+        let length = text[start..].len() - text[start..].trim_start_matches('`').len();
+        if length == run {
+            return Some(start + length);
+        }
+        i = start + length;
+    }
+    None
+}
+
+/// The length of the HTML tag, comment or autolink at the start of `text`.
+fn inline_html(text: &str) -> Option<usize> {
+    let next = text[1..].chars().next()?;
+    if !(next.is_ascii_alphabetic() || next == '/' || next == '!') {
+        return None;
+    }
+    text.find('>').map(|end| end + 1)
+}
+
+/// The length of the bare URL at the start of `text`, unless it is part of a word of `before`.
+fn bare_url(text: &str, before: &str) -> Option<usize> {
+    if before.ends_with(|c: char| c.is_alphanumeric()) {
+        return None;
+    }
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    Some(
+        text[..end]
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', ')'])
+            .len(),
+    )
+}
+
+/// A link `[text](url)`, `[text][reference]` or image `![alt](url)` at the start of `text`, as the
+/// end of the opening bracket, the end of the link text and the end of the link.
+fn link(text: &str) -> Option<(usize, usize, usize)> {
+    let open = match text.starts_with("![") {
+        true => 2,
+        false if text.starts_with('[') => 1,
+        false => return None,
+    };
+    let inner = open + closing(&text[open..], '[', ']')?;
+    let rest = &text[inner + 1..];
+    let close = match rest.chars().next()? {
+        '(' => closing(&rest[1..], '(', ')')? + 2,
+        '[' => rest.find(']')? + 1,
+        _ => return None,
+    };
+    Some((open, inner, inner + 1 + close))
+}
+
+/// The position of the `close` bracket that matches an already opened `open` bracket.
+fn closing(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return Some(i);
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Private helper that turns a translated [Prose::xml] back into Markdown.
+fn restore(xml: &str, protected: &[Protected]) -> String {
+    let mut markdown = String::with_capacity(xml.len());
+    let mut links = Vec::new();
+    let mut i = 0;
+    while let Some(c) = xml[i..].chars().next() {
+        let rest = &xml[i..];
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                let tag = &rest[1..end];
+                let placeholder = placeholder_id(tag).and_then(|id| protected.get(id));
+                match (tag, placeholder) {
+                    ("/l", _) => {
+                        if let Some(close) = links.pop() {
+                            markdown.push_str(close);
+                        }
+                    }
+                    (_, Some(Protected::Span(span))) => markdown.push_str(span),
+                    (_, Some(Protected::Link { open, close })) => {
+                        markdown.push_str(open);
+                        links.push(close);
+                    }
+                    (_, None) => markdown.push_str(&rest[..=end]),
+                }
+                i += end + 1;
+                continue;
+            }
+        }
+        if let Some((c, length)) = xml::entity(rest) {
+            markdown.push(c);
+            i += length;
+            continue;
+        }
+        markdown.push(c);
+        i += c.len_utf8();
+    }
+    markdown
+}
+
+/// The id of a placeholder tag like `x i="3"/` or `l i="3"`.
+fn placeholder_id(tag: &str) -> Option<usize> {
+    let attributes = tag.strip_prefix("x ").or_else(|| tag.strip_prefix("l "))?;
+    let id = attributes.trim().trim_end_matches('/').trim();
+    id.strip_prefix("i=\"")?.strip_suffix('"')?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    /// Reassembles a parsed document without translating it.
+    fn reassemble(markdown: &str) -> String {
+        parse(markdown)
+            .iter()
+            .map(|part| match part {
+                Part::Verbatim(markdown) => markdown.clone(),
+                Part::Prose(prose) => restore(&prose.xml, &prose.protected),
+            })
+            .collect()
+    }
+
+    fn prose(markdown: &str) -> Vec<String> {
+        parse(markdown)
+            .into_iter()
+            .filter_map(|part| match part {
+                Part::Prose(prose) => Some(prose.xml),
+                Part::Verbatim(_) => None,
+            })
+            .collect()
+    }
+
+    const DOCUMENT: &str = "---
+title: Hello
+---
+# Hello *world*
+
+This is a paragraph
+that wraps, with `inline code` and a [link](https://example.com/a_(b)).
+
+- [ ] Install with <kbd>cargo</kbd> & see https://docs.rs.
+- ![Logo](logo.png \"Logo\") 1 < 2
+  Second line
+
+```rust
+let x = \"Hello\";
+```
+
+    indented code
+
+> Quoted [reference][docs]
+<!-- Comment -->
+
+| Name | Value |
+| ---- | :---: |
+| a \\| b | c |
+
+[docs]: https://docs.rs
+";
+
+    #[test]
+    fn parse_markdown() {
+        assert_eq!(
+            prose(DOCUMENT),
+            vec![
+                "Hello *world*",
+                "This is a paragraph that wraps, with <x i=\"0\"/> and a <l i=\"1\">link</l>.",
+                "Install with <x i=\"0\"/>cargo<x i=\"1\"/> &amp; see <x i=\"2\"/>.",
+                "<l i=\"0\">Logo</l> 1 &lt; 2 Second line",
+                "Quoted <l i=\"0\">reference</l>",
+                "<x i=\"0\"/> Name <x i=\"1\"/> Value <x i=\"2\"/>",
+                "<x i=\"0\"/> a <x i=\"1\"/> b <x i=\"2\"/> c <x i=\"3\"/>",
+            ]
+        );
+        assert_eq!(
+            reassemble(DOCUMENT),
+            DOCUMENT
+                .replace("paragraph\nthat", "paragraph that")
+                .replace("2\n  Second", "2 Second")
+        );
+
+        assert!(prose("---\nno front matter").len() == 1);
+        assert!(prose("~~~~\nunterminated\n```\n~~~").is_empty());
+        assert_eq!(prose("1. One\n2) Two\n10 items"), ["One", "Two 10 items"]);
+    }
+
+    #[test]
+    fn restore_placeholders() {
+        let protected = [
+            Protected::Span("`code`".to_string()),
+            Protected::Link {
+                open: "[".to_string(),
+                close: "](url)".to_string(),
+            },
+        ];
+        assert_eq!(
+            restore(
+                "<l i=\"1\">Link</l> &amp; <x i=\"0\" /> <x i=\"7\"/> &lt;b&gt;",
+                &protected
+            ),
+            "[Link](url) & `code` <x i=\"7\"/> <b>"
+        );
+    }
+
+    #[test]
+    fn translate_markdown() {
+        // Translates "Hello" and "world" in each text.
+        let server =
+            MockServer::translating(|text| text.replace("Hello", "Hallo").replace("world", "Welt"));
+        let deepl = server.client();
+        let translated = deepl
+            .translate_markdown(DOCUMENT, Some(Language::En), "DE", None)
+            .unwrap();
+        assert!(translated.starts_with("---\ntitle: Hello\n---\n# Hallo *Welt*\n"));
+        assert!(translated.contains("let x = \"Hello\";"));
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(server.requests()[0].param_values("tag_handling"), ["xml"]);
+
+        let unchanged = "```\nHello\n```\n";
+        assert_eq!(
+            deepl
+                .translate_markdown(unchanged, None, "DE", None)
+                .unwrap(),
+            unchanged
+        );
+        assert_eq!(server.requests().len(), 1);
+    }
+}
@@ -0,0 +1,300 @@
+//! Metrics of API calls, e. g. to track the translation spend per service.
+//!
+//! Attach a [MetricsObserver] with [DeepL::with_metrics_observer](crate::DeepL::with_metrics_observer)
+//! to be notified after every call to the DeepL API, including failed ones. Forward the
+//! [RequestMetrics] to the metrics system of your choice, aggregate them in memory with the
+//! [MetricsCollector], or write an audit trail of all calls with the [RequestLogger].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use deepl_api::metrics::MetricsCollector;
+//! use deepl_api::*;
+//! use std::sync::Arc;
+//!
+//! let metrics = Arc::new(MetricsCollector::new());
+//! let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap(), false)
+//!     .with_metrics_observer(metrics.clone());
+//!
+//! let texts = TranslatableTextList::new(Language::De, vec!["Hello"]);
+//! deepl.translate(None, texts).unwrap();
+//!
+//! let translate = &metrics.snapshot()["translate"];
+//! assert_eq!(translate.requests, 1);
+//! assert_eq!(translate.characters, 5);
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// What happened during one call to the DeepL API.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct RequestMetrics {
+    /// The HTTP method of the call.
+    pub method: &'static str,
+    /// The endpoint: `translate`, `usage`, `languages` or `glossaries`.
+    pub endpoint: &'static str,
+    /// Time from sending the request until the response was parsed, including retries.
+    pub latency: Duration,
+    /// Number of characters submitted for translation, zero for other endpoints.
+    pub characters: u64,
+    /// Number of characters billed by DeepL, if it was reported for every text.
+    pub billed_characters: Option<u64>,
+    /// Billed minus computed characters, if they differ by more than the
+    /// [billing tolerance](crate::DeepL::with_billing_tolerance).
+    pub billing_discrepancy: Option<i64>,
+    /// The [kind](crate::Error::kind) of the error if the call failed.
+    pub error: Option<&'static str>,
+}
+
+/// Receives the [RequestMetrics] of every API call.
+///
+/// Observers are called synchronously on the thread that made the call, so they should return
+/// quickly.
+pub trait MetricsObserver: Send + Sync {
+    /// Called after each API call.
+    fn observe(&self, metrics: &RequestMetrics);
+}
+
+/// Aggregated metrics of one endpoint, see [MetricsCollector].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EndpointMetrics {
+    /// Number of calls.
+    pub requests: u64,
+    /// Number of failed calls by [error kind](crate::Error::kind).
+    pub failures: BTreeMap<&'static str, u64>,
+    /// Characters submitted for translation.
+    pub characters: u64,
+    /// Characters billed by DeepL, as far as reported.
+    pub billed_characters: u64,
+    /// Number of calls whose billed characters exceeded the
+    /// [billing tolerance](crate::DeepL::with_billing_tolerance).
+    pub billing_discrepancies: u64,
+    /// Sum of the latencies of all calls.
+    pub total_latency: Duration,
+    /// Highest latency of a call.
+    pub max_latency: Duration,
+}
+
+/// A [MetricsObserver] that aggregates the metrics per endpoint in memory.
+#[derive(Debug, Default)]
+pub struct MetricsCollector {
+    endpoints: Mutex<BTreeMap<&'static str, EndpointMetrics>>,
+}
+
+impl MetricsCollector {
+    /// Create an empty collector.
+    pub fn new() -> MetricsCollector {
+        MetricsCollector::default()
+    }
+
+    /// The metrics collected so far, by endpoint.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, EndpointMetrics> {
+        self.endpoints
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl MetricsObserver for MetricsCollector {
+    fn observe(&self, metrics: &RequestMetrics) {
+        let mut endpoints = self
+            .endpoints
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let endpoint = endpoints.entry(metrics.endpoint).or_default();
+        endpoint.requests += 1;
+        if let Some(kind) = metrics.error {
+            *endpoint.failures.entry(kind).or_default() += 1;
+        }
+        endpoint.characters += metrics.characters;
+        endpoint.billed_characters += metrics.billed_characters.unwrap_or(0);
+        endpoint.billing_discrepancies += u64::from(metrics.billing_discrepancy.is_some());
+        endpoint.total_latency += metrics.latency;
+        endpoint.max_latency = endpoint.max_latency.max(metrics.latency);
+    }
+}
+
+/// Where a [RequestLogger] writes to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Backend {
+    Log,
+    #[cfg(feature = "tracing")]
+    Tracing,
+}
+
+/// A [MetricsObserver] that logs one structured record per API call, e. g. for audit trails.
+///
+/// Each record holds the method, endpoint, number of characters submitted and billed, the
+/// duration and the outcome: `ok` or the [error kind](crate::Error::kind). The API key is never
+/// logged, as it is not part of the [RequestMetrics].
+///
+/// By default, records are written to the [log](https://docs.rs/log) crate at info level with
+/// the target `deepl_api::audit`, formatted as `key=value` pairs:
+///
+/// ```text
+/// method=POST endpoint=translate characters=11 billed_characters=11 duration_ms=183 outcome=ok
+/// ```
+///
+/// With the `tracing` feature, [to_tracing](RequestLogger::to_tracing) emits them as events with
+/// the same fields instead.
+#[derive(Clone, Debug)]
+pub struct RequestLogger {
+    level: log::Level,
+    backend: Backend,
+}
+
+impl Default for RequestLogger {
+    fn default() -> RequestLogger {
+        RequestLogger {
+            level: log::Level::Info,
+            backend: Backend::Log,
+        }
+    }
+}
+
+impl RequestLogger {
+    /// Log records to the `log` crate at info level.
+    pub fn new() -> RequestLogger {
+        RequestLogger::default()
+    }
+
+    /// Log records at `level` instead.
+    pub fn with_level(mut self, level: log::Level) -> RequestLogger {
+        self.level = level;
+        self
+    }
+
+    /// Emit records as `tracing` events instead of `log` records.
+    #[cfg(feature = "tracing")]
+    pub fn to_tracing(mut self) -> RequestLogger {
+        self.backend = Backend::Tracing;
+        self
+    }
+
+    /// Private method that formats a record for the `log` crate.
+    fn format(metrics: &RequestMetrics) -> String {
+        let billed_characters = match metrics.billed_characters {
+            Some(billed_characters) => billed_characters.to_string(),
+            None => "-".to_string(),
+        };
+        format!(
+            "method={} endpoint={} characters={} billed_characters={} duration_ms={} outcome={}",
+            metrics.method,
+            metrics.endpoint,
+            metrics.characters,
+            billed_characters,
+            metrics.latency.as_millis(),
+            metrics.error.unwrap_or("ok"),
+        )
+    }
+}
+
+impl MetricsObserver for RequestLogger {
+    fn observe(&self, metrics: &RequestMetrics) {
+        match self.backend {
+            Backend::Log => log::log!(
+                target: "deepl_api::audit",
+                self.level,
+                "{}",
+                RequestLogger::format(metrics)
+            ),
+            #[cfg(feature = "tracing")]
+            Backend::Tracing => {
+                macro_rules! event {
+                    ($level:expr) => {
+                        tracing::event!(
+                            target: "deepl_api::audit",
+                            $level,
+                            method = metrics.method,
+                            endpoint = metrics.endpoint,
+                            characters = metrics.characters,
+                            billed_characters = metrics.billed_characters,
+                            duration_ms = metrics.latency.as_millis() as u64,
+                            outcome = metrics.error.unwrap_or("ok"),
+                        )
+                    };
+                }
+                match self.level {
+                    log::Level::Error => event!(tracing::Level::ERROR),
+                    log::Level::Warn => event!(tracing::Level::WARN),
+                    log::Level::Info => event!(tracing::Level::INFO),
+                    log::Level::Debug => event!(tracing::Level::DEBUG),
+                    log::Level::Trace => event!(tracing::Level::TRACE),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::{DeepL, Language, TranslatableTextList};
+    use std::sync::Arc;
+
+    #[test]
+    fn metrics_collector() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/v2/usage" => MockResponse::json(500, "{}"),
+            _ => MockResponse::json(
+                200,
+                r#"{"translations": [{"detected_source_language": "EN", "text": "Hallo", "billed_characters": 5},
+                                     {"detected_source_language": "EN", "text": "Welt", "billed_characters": 5}]}"#,
+            ),
+        });
+        let metrics = Arc::new(MetricsCollector::new());
+        let deepl = DeepL::new("key".to_string(), false)
+            .with_base_url(server.url())
+            .with_metrics_observer(metrics.clone());
+
+        let texts = TranslatableTextList::new(Language::De, vec!["Hello", "World"]);
+        deepl.translate(None, texts).unwrap();
+        // One text too few in the response.
+        let texts = TranslatableTextList::new(Language::De, vec!["Hello", "World", "!"]);
+        deepl.translate(None, texts).unwrap_err();
+        deepl.usage_information().unwrap_err();
+
+        let snapshot = metrics.snapshot();
+        let translate = &snapshot["translate"];
+        assert_eq!(translate.requests, 2);
+        assert_eq!(translate.characters, 21);
+        assert_eq!(translate.billed_characters, 10);
+        assert_eq!(translate.failures["deserialization"], 1);
+        assert!(translate.max_latency <= translate.total_latency);
+        let usage = &snapshot["usage"];
+        assert_eq!(usage.requests, 1);
+        assert_eq!(usage.failures["server"], 1);
+        assert!(!snapshot.contains_key("languages"));
+    }
+
+    #[test]
+    fn request_logger() {
+        let mut metrics = RequestMetrics {
+            method: "POST",
+            endpoint: "translate",
+            latency: Duration::from_millis(183),
+            characters: 11,
+            billed_characters: Some(11),
+            billing_discrepancy: None,
+            error: None,
+        };
+        assert_eq!(
+            RequestLogger::format(&metrics),
+            "method=POST endpoint=translate characters=11 billed_characters=11 duration_ms=183 outcome=ok"
+        );
+        metrics.billed_characters = None;
+        metrics.error = Some("quota_exceeded");
+        assert_eq!(
+            RequestLogger::format(&metrics),
+            "method=POST endpoint=translate characters=11 billed_characters=- duration_ms=183 outcome=quota_exceeded"
+        );
+
+        // Must not panic without a logger.
+        RequestLogger::new().observe(&metrics);
+    }
+}
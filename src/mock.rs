@@ -0,0 +1,271 @@
+//! An offline stand-in for [DeepL](crate::DeepL), for the test suites of downstream crates.
+//!
+//! [MockDeepL] implements the [Translator] trait without any network access. It answers with
+//! canned translations, keeps track of the translated characters like the real usage endpoint and
+//! remembers all translation requests for assertions. Only available with the `mock` feature,
+//! which is typically enabled for dev-dependencies only:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! deepl-api = { version = "0.2", features = ["mock"] }
+//! ```
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::mock::MockDeepL;
+//! use deepl_api::*;
+//!
+//! let deepl = MockDeepL::new().with_translation(Language::De, "Hello", "Hallo");
+//!
+//! let texts = TranslatableTextList::new(Language::De, vec!["Hello", "World"]);
+//! let translated = deepl.translate(None, texts).unwrap();
+//! assert_eq!(translated[0].text, "Hallo");
+//! // Texts without a canned translation are returned unchanged.
+//! assert_eq!(translated[1].text, "World");
+//! assert_eq!(deepl.usage_information().unwrap().character_count, 10);
+//! ```
+
+use crate::{
+    billable_characters, Error, Language, LanguageInformation, LanguageList, Result,
+    TranslatableTextList, TranslatedText, TranslationOptions, Translator, UsageInformation,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Source language reported for texts without an explicit source language.
+const DETECTED_SOURCE_LANGUAGE: &str = "EN";
+
+/// A [Translator] with canned responses, see the [module documentation](self).
+#[derive(Debug)]
+pub struct MockDeepL {
+    character_limit: u64,
+    character_count: Mutex<u64>,
+    source_languages: LanguageList,
+    target_languages: LanguageList,
+    translations: HashMap<(Language, String), String>,
+    requests: Mutex<Vec<TranslatableTextList>>,
+}
+
+impl Default for MockDeepL {
+    fn default() -> MockDeepL {
+        MockDeepL {
+            character_limit: 500000,
+            character_count: Mutex::new(0),
+            source_languages: languages(&[
+                ("DE", "German", None),
+                ("EN", "English", None),
+                ("ES", "Spanish", None),
+                ("FR", "French", None),
+                ("IT", "Italian", None),
+                ("JA", "Japanese", None),
+            ]),
+            target_languages: languages(&[
+                ("DE", "German", Some(true)),
+                ("EN-GB", "English (British)", Some(false)),
+                ("EN-US", "English (American)", Some(false)),
+                ("ES", "Spanish", Some(true)),
+                ("FR", "French", Some(true)),
+                ("IT", "Italian", Some(true)),
+                ("JA", "Japanese", Some(true)),
+            ]),
+            translations: HashMap::new(),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl MockDeepL {
+    /// Create a mock with a quota of 500000 characters and a few common languages, which
+    /// returns all texts unchanged.
+    pub fn new() -> MockDeepL {
+        MockDeepL::default()
+    }
+
+    /// Translate `text` into `target_language` as `translation`.
+    pub fn with_translation(
+        mut self,
+        target_language: impl Into<Language>,
+        text: impl Into<String>,
+        translation: impl Into<String>,
+    ) -> MockDeepL {
+        self.translations
+            .insert((target_language.into(), text.into()), translation.into());
+        self
+    }
+
+    /// Set the characters already used and the character limit. Translations beyond the limit
+    /// fail with [QuotaExceeded](Error::QuotaExceeded).
+    pub fn with_usage(mut self, character_count: u64, character_limit: u64) -> MockDeepL {
+        self.character_count = Mutex::new(character_count);
+        self.character_limit = character_limit;
+        self
+    }
+
+    /// Replace the available source languages.
+    pub fn with_source_languages(mut self, source_languages: LanguageList) -> MockDeepL {
+        self.source_languages = source_languages;
+        self
+    }
+
+    /// Replace the available target languages. Translations into other languages fail with a
+    /// [ServerError](Error::ServerError), like the real API does.
+    pub fn with_target_languages(mut self, target_languages: LanguageList) -> MockDeepL {
+        self.target_languages = target_languages;
+        self
+    }
+
+    /// All text lists passed to [translate](Translator::translate) so far, in order.
+    pub fn requests(&self) -> Vec<TranslatableTextList> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl Translator for MockDeepL {
+    fn usage_information(&self) -> Result<UsageInformation> {
+        Ok(UsageInformation {
+            character_limit: self.character_limit,
+            character_count: *self
+                .character_count
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            ..UsageInformation::default()
+        })
+    }
+
+    fn source_languages(&self) -> Result<LanguageList> {
+        Ok(self.source_languages.clone())
+    }
+
+    fn target_languages(&self) -> Result<LanguageList> {
+        Ok(self.target_languages.clone())
+    }
+
+    fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(text_list.clone());
+
+        let target_language = Language::from(&text_list.target_language);
+        let supported = self
+            .target_languages
+            .iter()
+            .any(|l| Language::from(&l.language) == target_language);
+        if !supported {
+            return Err(Error::ServerError {
+                message: "Value for 'target_lang' not supported.".to_string(),
+                status: 400,
+                trace_id: None,
+            });
+        }
+
+        let characters: u64 = text_list
+            .texts
+            .iter()
+            .map(|text| billable_characters(text))
+            .sum();
+        let mut character_count = self
+            .character_count
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if *character_count + characters > self.character_limit {
+            return Err(Error::QuotaExceeded);
+        }
+        *character_count += characters;
+
+        let show_billed_characters = options.and_then(|o| o.show_billed_characters) == Some(true);
+        let detected_source_language = text_list
+            .source_language
+            .clone()
+            .unwrap_or_else(|| DETECTED_SOURCE_LANGUAGE.to_string());
+        Ok(text_list
+            .texts
+            .into_iter()
+            .map(|text| {
+                let billed_characters = match show_billed_characters {
+                    true => Some(billable_characters(&text)),
+                    false => None,
+                };
+                let key = (target_language.clone(), text);
+                let text = match self.translations.get(&key) {
+                    Some(translation) => translation.clone(),
+                    None => key.1,
+                };
+                TranslatedText {
+                    detected_source_language: detected_source_language.clone(),
+                    text,
+                    billed_characters,
+                    model_type_used: None,
+                }
+            })
+            .collect())
+    }
+}
+
+fn languages(languages: &[(&str, &str, Option<bool>)]) -> LanguageList {
+    languages
+        .iter()
+        .map(
+            |&(language, name, supports_formality)| LanguageInformation {
+                language: language.to_string(),
+                name: name.to_string(),
+                supports_formality,
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_deepl() {
+        let deepl = MockDeepL::new()
+            .with_usage(0, 12)
+            .with_translation("de", "Hello", "Hallo");
+
+        let options = TranslationOptions::new().show_billed_characters(true);
+        let texts = TranslatableTextList::new(Language::De, vec!["Hello"])
+            .with_source_language(Language::En);
+        let translated = deepl.translate(Some(options), texts).unwrap();
+        assert_eq!(
+            translated,
+            vec![TranslatedText {
+                detected_source_language: "EN".to_string(),
+                text: "Hallo".to_string(),
+                billed_characters: Some(5),
+                model_type_used: None,
+            }]
+        );
+
+        // Canned translations are per target language.
+        let texts = TranslatableTextList::new(Language::Fr, vec!["Hello"]);
+        assert_eq!(deepl.translate(None, texts).unwrap()[0].text, "Hello");
+        assert_eq!(deepl.usage_information().unwrap().character_count, 10);
+
+        let texts = TranslatableTextList::new(Language::Fr, vec!["Hello"]);
+        assert!(matches!(
+            deepl.translate(None, texts),
+            Err(Error::QuotaExceeded)
+        ));
+        let texts = TranslatableTextList::new("XX", vec!["Hello"]);
+        assert!(matches!(
+            deepl.translate(None, texts),
+            Err(Error::ServerError { status: 400, .. })
+        ));
+        assert_eq!(deepl.usage_information().unwrap().character_count, 10);
+
+        assert_eq!(deepl.requests().len(), 4);
+        assert_eq!(deepl.requests()[1].target_language, "FR");
+        assert_eq!(deepl.source_languages().unwrap().len(), 6);
+    }
+}
@@ -0,0 +1,217 @@
+//! Protection of format string placeholders from translation, see
+//! [DeepL::with_placeholder_protection](crate::DeepL::with_placeholder_protection).
+//!
+//! [protect] turns a text into XML in which each placeholder is wrapped in an `<x>` tag, to be
+//! translated with [tag_handling](crate::TranslationOptions::tag_handling) `xml` and `x` as
+//! [ignore tag](crate::TranslationOptions::ignore_tags). [restore] turns the translation back into
+//! plain text and checks that it contains the same placeholders as the source text.
+//!
+//! Recognized placeholders are `{name}`, `{0}` and `{name:>10}` (Rust, Python, C#), `{{name}}`
+//! and `{{{name}}}` (Handlebars, Mustache), and printf-style conversions like `%s`, `%1$d`,
+//! `%(count)d` and `%.2f`.
+//!
+//! ```rust
+//! use deepl_api::placeholders::{placeholders, protect, restore};
+//!
+//! let text = "Hello {name}, you have %(count)d new messages & {{unread}} unread.";
+//! assert_eq!(placeholders(text), ["{name}", "%(count)d", "{{unread}}"]);
+//! assert_eq!(
+//!     protect(text),
+//!     "Hello <x>{name}</x>, you have <x>%(count)d</x> new messages &amp; <x>{{unread}}</x> unread."
+//! );
+//!
+//! let translated = "Hallo <x>{name}</x>, Sie haben <x>%(count)d</x> neue Nachrichten &amp; <x>{{unread}}</x> ungelesen.";
+//! assert_eq!(
+//!     restore(translated, text).unwrap(),
+//!     "Hallo {name}, Sie haben %(count)d neue Nachrichten & {{unread}} ungelesen."
+//! );
+//! assert!(restore("Hallo <x>{name}</x>.", text).is_err());
+//! ```
+
+use crate::xml::{escape_into, unescape};
+use crate::{Error, Result};
+
+/// The tag that wraps placeholders in [protect]ed texts.
+pub const PLACEHOLDER_TAG: &str = "x";
+
+/// The placeholders in `text`, in order.
+pub fn placeholders(text: &str) -> Vec<&str> {
+    let mut placeholders = Vec::new();
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        match placeholder(&text[i..]) {
+            Some(length) => {
+                placeholders.push(&text[i..i + length]);
+                i += length;
+            }
+            None => i += c.len_utf8(),
+        }
+    }
+    placeholders
+}
+
+/// Escape `text` as XML and wrap its placeholders in [PLACEHOLDER_TAG] tags.
+pub fn protect(text: &str) -> String {
+    let mut xml = String::with_capacity(text.len());
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        match placeholder(&text[i..]) {
+            Some(length) => {
+                xml.push_str(&format!("<{}>", PLACEHOLDER_TAG));
+                escape_into(&text[i..i + length], &mut xml);
+                xml.push_str(&format!("</{}>", PLACEHOLDER_TAG));
+                i += length;
+            }
+            None => {
+                escape_into(&text[i..i + c.len_utf8()], &mut xml);
+                i += c.len_utf8();
+            }
+        }
+    }
+    xml
+}
+
+/// Turn the translation of a [protect]ed text back into plain text. Fails with
+/// [PlaceholderMismatch](Error::PlaceholderMismatch) unless the translation has the same
+/// placeholders as `source`, in any order.
+pub fn restore(translated: &str, source: &str) -> Result<String> {
+    let open = format!("<{}>", PLACEHOLDER_TAG);
+    let close = format!("</{}>", PLACEHOLDER_TAG);
+    let (mut text, mut found) = (String::with_capacity(translated.len()), Vec::new());
+    let mut rest = translated;
+    while let Some(start) = rest.find(&open) {
+        let end = match rest[start..].find(&close) {
+            Some(end) => start + end,
+            None => break,
+        };
+        text.push_str(&unescape(&rest[..start]));
+        let placeholder = unescape(&rest[start + open.len()..end]);
+        text.push_str(&placeholder);
+        found.push(placeholder);
+        rest = &rest[end + close.len()..];
+    }
+    text.push_str(&unescape(rest));
+
+    let expected: Vec<String> = placeholders(source).into_iter().map(String::from).collect();
+    let (mut sorted_expected, mut sorted_found) = (expected.clone(), found.clone());
+    sorted_expected.sort();
+    sorted_found.sort();
+    if sorted_expected != sorted_found {
+        return Err(Error::PlaceholderMismatch { expected, found });
+    }
+    Ok(text)
+}
+
+/// The length of the placeholder at the start of `text`, if any.
+fn placeholder(text: &str) -> Option<usize> {
+    if text.starts_with("{{") {
+        let braces = text.len() - text.trim_start_matches('{').len();
+        let closing = "}".repeat(braces.min(3));
+        return text[braces..]
+            .find(&closing)
+            .map(|end| braces + end + closing.len());
+    }
+    if let Some(inner) = text.strip_prefix('{') {
+        let end = inner.find('}')?;
+        let name = &inner[..end];
+        let valid = !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == '{');
+        return valid.then_some(end + 2);
+    }
+    if text.starts_with('%') {
+        return printf_conversion(text);
+    }
+    None
+}
+
+/// The length of the printf-style conversion at the start of `text`, like `%-5.2f` or `%(name)s`.
+fn printf_conversion(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = 1;
+    if bytes.get(i) == Some(&b'%') {
+        return Some(2);
+    }
+    if bytes.get(i) == Some(&b'(') {
+        i += text[i..].find(')')? + 1;
+    }
+    let digits = |i: usize| {
+        bytes[i..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit() || **b == b'*')
+            .count()
+    };
+    // Positional argument like `%1$s`.
+    let position = digits(i);
+    if position > 0 && bytes.get(i + position) == Some(&b'$') {
+        i += position + 1;
+    }
+    // Flags without space, so that "50% off" is not a placeholder.
+    while matches!(bytes.get(i), Some(b'-' | b'+' | b'#' | b'0' | b'\'')) {
+        i += 1;
+    }
+    i += digits(i);
+    if bytes.get(i) == Some(&b'.') {
+        i += 1 + digits(i + 1);
+    }
+    while matches!(
+        bytes.get(i),
+        Some(b'h' | b'l' | b'L' | b'q' | b'j' | b'z' | b't')
+    ) {
+        i += 1;
+    }
+    match bytes.get(i) {
+        Some(b'd' | b'i' | b'o' | b'u' | b'x' | b'X' | b'e' | b'E' | b'f' | b'F' | b'g' | b'G')
+        | Some(b'a' | b'A' | b'c' | b's' | b'p' | b'n' | b'@') => Some(i + 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognize_placeholders() {
+        let text = "{0} {name:>10} {{{raw}}} %1$s %-5.2f %lld %% %@ {not a placeholder} {} 50% off";
+        assert_eq!(
+            placeholders(text),
+            [
+                "{0}",
+                "{name:>10}",
+                "{{{raw}}}",
+                "%1$s",
+                "%-5.2f",
+                "%lld",
+                "%%",
+                "%@"
+            ]
+        );
+        assert_eq!(
+            placeholders("{{#if a > b}}yes{{/if}}"),
+            ["{{#if a > b}}", "{{/if}}"]
+        );
+        assert_eq!(
+            protect("{{#if a > b}}<b>"),
+            "<x>{{#if a &gt; b}}</x>&lt;b&gt;"
+        );
+        assert!(placeholders("{unclosed %( {{open").is_empty());
+    }
+
+    #[test]
+    fn restore_placeholders() {
+        let source = "%s & %d {a}";
+        let translated = "<x>%d</x> &amp; <x>%s</x> <x>{a}</x>";
+        assert_eq!(restore(translated, source).unwrap(), "%d & %s {a}");
+
+        match restore("<x>%d</x> <x>%d</x> <x>{a}</x>", source) {
+            Err(Error::PlaceholderMismatch { expected, found }) => {
+                assert_eq!(expected, ["%s", "%d", "{a}"]);
+                assert_eq!(found, ["%d", "%d", "{a}"]);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert_eq!(
+            restore("Nothing &lt;here&gt;", "Nothing <here>").unwrap(),
+            "Nothing <here>"
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Durable queue of translation jobs, for pipelines that must survive network failures and
+//! process restarts.
+//!
+//! A [JobQueue] keeps its jobs in a [Store], e. g. a [FileStore](crate::store::FileStore) next to
+//! the files of a nightly localization run. [Enqueued](JobQueue::enqueue) jobs are translated by
+//! [process_due](JobQueue::process_due) or [drain](JobQueue::drain), and their translations are
+//! persisted along with them until they are [removed](JobQueue::remove).
+//!
+//! Jobs that fail with a [retriable](crate::Error::is_retriable) error, e. g. because the network
+//! is down, are rescheduled with the backoff of the queue's [RetryPolicy] and fail for good once
+//! its `max_attempts` are used up. All other errors fail the job right away. A job is only
+//! updated after its request completed, so if the process is killed in between, the job is
+//! translated again by the next run.
+//!
+//! Only one process should work on a queue at a time.
+//!
+//! ```rust,no_run
+//! use deepl_api::queue::JobQueue;
+//! use deepl_api::store::FileStore;
+//! use deepl_api::*;
+//! use std::sync::Arc;
+//!
+//! let queue = JobQueue::new(Arc::new(FileStore::open("l10n-queue").unwrap()));
+//! let id = queue
+//!     .enqueue(None, TranslatableTextList::new(Language::De, ["Hello", "Goodbye"]))
+//!     .unwrap();
+//!
+//! let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap(), false);
+//! let report = queue.drain(&deepl).unwrap();
+//! println!("{} jobs completed, {} failed", report.completed, report.failed);
+//! println!("{:?}", queue.job(&id).unwrap().unwrap().translations);
+//! ```
+
+use crate::store::Store;
+use crate::{
+    DeepL, Error, Result, RetryPolicy, TranslatableTextList, TranslatedText, TranslationOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NAMESPACE: &str = "queue";
+
+/// The processing state of a [Job].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Waiting to be translated, either for the first time or again after a transient failure.
+    Pending,
+    /// Translated, see [Job::translations].
+    Completed,
+    /// Given up, see [Job::error].
+    Failed,
+}
+
+/// A translation job of the [JobQueue].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Job {
+    /// Identifies the job in the queue. Jobs are processed in the order of their ids.
+    #[serde(skip)]
+    pub id: String,
+    /// The options to translate with.
+    pub options: Option<TranslationOptions>,
+    /// The texts to translate.
+    pub text_list: TranslatableTextList,
+    /// The processing state.
+    pub state: JobState,
+    /// How many translation attempts were made so far.
+    pub attempts: u32,
+    /// Milliseconds since the unix epoch before which the job is not attempted again.
+    pub not_before: u64,
+    /// The error of the last failed attempt, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The translations, once the job is completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translations: Option<Vec<TranslatedText>>,
+}
+
+/// What [JobQueue::process_due] or [JobQueue::drain] did.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct QueueReport {
+    /// Number of jobs that were translated.
+    pub completed: usize,
+    /// Number of jobs that failed transiently and were rescheduled.
+    pub rescheduled: usize,
+    /// Number of jobs that failed for good.
+    pub failed: usize,
+}
+
+/// Persists translation jobs and their results in a [Store].
+pub struct JobQueue {
+    store: Arc<dyn Store>,
+    retry_policy: RetryPolicy,
+    sequence: AtomicU64,
+}
+
+impl JobQueue {
+    /// Create a queue that keeps its jobs in the namespace `queue` of `store`, and retries
+    /// according to the default [RetryPolicy].
+    pub fn new(store: Arc<dyn Store>) -> JobQueue {
+        JobQueue {
+            store,
+            retry_policy: RetryPolicy::default(),
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    /// Schedule retries of transiently failed jobs according to `retry_policy`.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> JobQueue {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Add a job to translate `text_list` with `options`, and return its id.
+    pub fn enqueue(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<String> {
+        let now = unix_millis(SystemTime::now());
+        // Zero-padded keys keep the jobs ordered by time; the sequence number and process id
+        // keep keys unique if several jobs share a timestamp.
+        let id = format!(
+            "{:020}-{:010}-{:020}",
+            now,
+            std::process::id(),
+            self.sequence.fetch_add(1, Ordering::SeqCst)
+        );
+        self.save(&Job {
+            id: id.clone(),
+            options,
+            text_list,
+            state: JobState::Pending,
+            attempts: 0,
+            not_before: now,
+            error: None,
+            translations: None,
+        })?;
+        Ok(id)
+    }
+
+    /// The job with the given id, if it is in the queue.
+    pub fn job(&self, id: &str) -> Result<Option<Job>> {
+        self.store
+            .get(NAMESPACE, id)?
+            .map(|value| parse(id.to_string(), &value))
+            .transpose()
+    }
+
+    /// All jobs, in the order they were enqueued.
+    pub fn jobs(&self) -> Result<Vec<Job>> {
+        self.store
+            .scan(NAMESPACE, "")?
+            .into_iter()
+            .map(|(id, value)| parse(id, &value))
+            .collect()
+    }
+
+    /// Remove a job, e. g. after its translations were picked up. Does nothing if there is none.
+    pub fn remove(&self, id: &str) -> Result<()> {
+        self.store.delete(NAMESPACE, id)
+    }
+
+    /// Make a failed job pending again, with a fresh budget of attempts. Returns whether the job
+    /// was failed.
+    pub fn requeue(&self, id: &str) -> Result<bool> {
+        let mut job = match self.job(id)? {
+            Some(job) if job.state == JobState::Failed => job,
+            _ => return Ok(false),
+        };
+        job.state = JobState::Pending;
+        job.attempts = 0;
+        job.not_before = unix_millis(SystemTime::now());
+        self.save(&job)?;
+        Ok(true)
+    }
+
+    /// Attempt all pending jobs that are due, once, in order. Jobs that are not due yet are left
+    /// for later runs.
+    ///
+    /// Translation failures are recorded in the jobs; only failures of the store are returned.
+    pub fn process_due(&self, deepl: &DeepL) -> Result<QueueReport> {
+        let mut report = QueueReport::default();
+        for job in self.jobs()? {
+            if job.state == JobState::Pending && job.not_before <= unix_millis(SystemTime::now()) {
+                self.attempt(deepl, job, &mut report)?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Process the queue until no job is pending any more, waiting for rescheduled jobs to
+    /// become due.
+    pub fn drain(&self, deepl: &DeepL) -> Result<QueueReport> {
+        let mut report = QueueReport::default();
+        loop {
+            let pass = self.process_due(deepl)?;
+            report.completed += pass.completed;
+            report.rescheduled += pass.rescheduled;
+            report.failed += pass.failed;
+
+            let next = self
+                .jobs()?
+                .into_iter()
+                .filter(|job| job.state == JobState::Pending)
+                .map(|job| job.not_before)
+                .min();
+            match next {
+                Some(not_before) => {
+                    let now = unix_millis(SystemTime::now());
+                    std::thread::sleep(Duration::from_millis(not_before.saturating_sub(now)));
+                }
+                None => return Ok(report),
+            }
+        }
+    }
+
+    fn attempt(&self, deepl: &DeepL, mut job: Job, report: &mut QueueReport) -> Result<()> {
+        job.attempts += 1;
+        match deepl.translate(job.options.clone(), job.text_list.clone()) {
+            Ok(translations) => {
+                job.state = JobState::Completed;
+                job.error = None;
+                job.translations = Some(translations);
+                report.completed += 1;
+            }
+            Err(e) => {
+                if e.is_retriable() && job.attempts < self.retry_policy.max_attempts {
+                    let delay = e
+                        .retry_after()
+                        .unwrap_or_else(|| self.retry_policy.backoff(job.attempts));
+                    job.not_before = unix_millis(SystemTime::now() + delay);
+                    report.rescheduled += 1;
+                } else {
+                    job.state = JobState::Failed;
+                    report.failed += 1;
+                }
+                log::warn!("Translation job {} failed: {}", job.id, e);
+                job.error = Some(e.to_string());
+            }
+        }
+        self.save(&job)
+    }
+
+    fn save(&self, job: &Job) -> Result<()> {
+        let value = serde_json::to_vec(job).map_err(|e| Error::StorageError(e.to_string()))?;
+        self.store.put(NAMESPACE, &job.id, &value)
+    }
+}
+
+fn parse(id: String, value: &[u8]) -> Result<Job> {
+    let job: Job = serde_json::from_slice(value).map_err(|e| Error::StorageError(e.to_string()))?;
+    Ok(Job { id, ..job })
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{FileStore, MemoryStore};
+    use crate::testing::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn retry_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn drain_with_retries() {
+        // Fails every text twice with a server error, and "Fehler" with a bad request.
+        let requests = AtomicUsize::new(0);
+        let server = MockServer::start(move |request| {
+            let text = request.param_values("text").remove(0);
+            if text == "Fehler" {
+                return MockResponse::json(400, r#"{"message": "Bad request"}"#);
+            }
+            if requests.fetch_add(1, Ordering::SeqCst) % 3 < 2 {
+                return MockResponse::json(503, r#"{"message": "Unavailable"}"#);
+            }
+            let body = serde_json::json!({
+                "translations": [{"detected_source_language": "DE", "text": text.to_uppercase()}]
+            });
+            MockResponse::json(200, &body.to_string())
+        });
+        let deepl = server.client();
+
+        let queue = JobQueue::new(Arc::new(MemoryStore::new())).with_retry_policy(retry_policy(3));
+        let options = TranslationOptions::new().preserve_formatting(true);
+        let done = queue
+            .enqueue(
+                Some(options.clone()),
+                TranslatableTextList::new("EN", ["ja"]),
+            )
+            .unwrap();
+        let bad = queue
+            .enqueue(None, TranslatableTextList::new("EN", ["Fehler"]))
+            .unwrap();
+
+        let report = queue.drain(&deepl).unwrap();
+        assert_eq!(
+            report,
+            QueueReport {
+                completed: 1,
+                rescheduled: 2,
+                failed: 1
+            }
+        );
+        let job = queue.job(&done).unwrap().unwrap();
+        assert_eq!(job.state, JobState::Completed);
+        assert_eq!(job.attempts, 3);
+        assert_eq!(job.options, Some(options));
+        assert_eq!(job.error, None);
+        assert_eq!(job.translations.unwrap()[0].text, "JA");
+        assert_eq!(
+            server.requests()[2].param_values("preserve_formatting"),
+            ["1"]
+        );
+
+        let job = queue.job(&bad).unwrap().unwrap();
+        assert_eq!(job.state, JobState::Failed);
+        assert_eq!(job.attempts, 1);
+        assert!(job.error.is_some());
+        assert_eq!(
+            queue
+                .jobs()
+                .unwrap()
+                .iter()
+                .map(|job| &job.id)
+                .collect::<Vec<_>>(),
+            [&done, &bad]
+        );
+
+        assert!(queue.requeue(&bad).unwrap());
+        assert!(!queue.requeue(&done).unwrap());
+        assert_eq!(queue.job(&bad).unwrap().unwrap().state, JobState::Pending);
+        queue.remove(&bad).unwrap();
+        assert_eq!(queue.job(&bad).unwrap(), None);
+    }
+
+    #[test]
+    fn give_up_and_resume() {
+        let server = MockServer::start(|_| MockResponse::json(500, r#"{"message": "Down"}"#));
+        let root = std::env::temp_dir().join(format!("deepl-queue-test-{}", std::process::id()));
+        let store = Arc::new(FileStore::open(&root).unwrap());
+
+        let queue = JobQueue::new(store.clone()).with_retry_policy(retry_policy(2));
+        let id = queue
+            .enqueue(None, TranslatableTextList::new("DE", ["Hello"]))
+            .unwrap();
+        let report = queue.drain(&server.client()).unwrap();
+        assert_eq!((report.rescheduled, report.failed), (1, 1));
+        assert_eq!(server.requests().len(), 2);
+
+        // A new queue on the same store, e. g. after a restart, picks up the requeued job.
+        queue.requeue(&id).unwrap();
+        let queue = JobQueue::new(store).with_retry_policy(retry_policy(2));
+        let server = MockServer::deepl();
+        let report = queue.process_due(&server.client()).unwrap();
+        assert_eq!(report.completed, 1);
+        assert_eq!(queue.job(&id).unwrap().unwrap().state, JobState::Completed);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
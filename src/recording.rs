@@ -0,0 +1,247 @@
+//! Record and replay of API responses, for deterministic tests.
+//!
+//! A [Recorder] wraps a [Translator] and stores its responses in a JSON fixture file, a so-called
+//! cassette. On the first run, requests go to the wrapped translator and the responses are
+//! recorded; later runs replay them from the cassette without network access or spending quota.
+//! Commit the cassette along with the tests, and delete it (or use [RecordMode::Record]) to
+//! record it anew.
+//!
+//! Only successful responses are recorded. Requests are matched exactly, including the
+//! [options](crate::TranslationOptions), and may be replayed any number of times.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use deepl_api::recording::{RecordMode, Recorder};
+//! use deepl_api::*;
+//!
+//! let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap_or_default(), false);
+//! let deepl = Recorder::new(deepl, "tests/cassettes/greeting.json")
+//!     .unwrap()
+//!     .with_mode(RecordMode::Auto);
+//!
+//! let texts = TranslatableTextList::new(Language::De, vec!["Hello"]);
+//! assert_eq!(deepl.translate(None, texts).unwrap()[0].text, "Hallo");
+//! ```
+
+use crate::{
+    Error, LanguageList, Result, TranslatableTextList, TranslatedText, TranslationOptions,
+    Translator, UsageInformation,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Controls when a [Recorder] talks to the wrapped translator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordMode {
+    /// Replay recorded responses, and record the responses of new requests. This is the default.
+    Auto,
+    /// Only replay. Requests without a recorded response fail, which is useful on CI to make sure
+    /// that no request slips through to the API.
+    Replay,
+    /// Send all requests to the wrapped translator and record the responses, replacing earlier
+    /// recordings of the same requests.
+    Record,
+}
+
+/// One recorded request with its response.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Interaction {
+    request: Value,
+    response: Value,
+}
+
+/// A [Translator] that records and replays the responses of another one, see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct Recorder<T> {
+    inner: T,
+    path: PathBuf,
+    mode: RecordMode,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl<T: Translator> Recorder<T> {
+    /// Wrap `inner`, with the cassette at `path`. The cassette is loaded if it exists, and created
+    /// with the first recording otherwise.
+    pub fn new(inner: T, path: impl Into<PathBuf>) -> Result<Recorder<T>> {
+        let path = path.into();
+        let interactions = match fs::read(&path) {
+            Ok(content) => {
+                serde_json::from_slice(&content).map_err(|_| Error::DeserializationError)?
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Recorder {
+            inner,
+            path,
+            mode: RecordMode::Auto,
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    /// Set the [RecordMode].
+    pub fn with_mode(mut self, mode: RecordMode) -> Recorder<T> {
+        self.mode = mode;
+        self
+    }
+
+    /// The path of the cassette.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the wrapped translator.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Replay the response to `request`, or get it from `live` and record it.
+    fn call<R: Serialize + DeserializeOwned>(
+        &self,
+        request: Value,
+        live: impl FnOnce(&T) -> Result<R>,
+    ) -> Result<R> {
+        if self.mode != RecordMode::Record {
+            let interactions = self
+                .interactions
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Some(interaction) = interactions.iter().find(|i| i.request == request) {
+                return serde_json::from_value(interaction.response.clone())
+                    .map_err(|_| Error::DeserializationError);
+            }
+        }
+        if self.mode == RecordMode::Replay {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "no recorded response for {} in {}",
+                    request,
+                    self.path.display()
+                ),
+            )
+            .into());
+        }
+
+        let response = live(&self.inner)?;
+        let mut interactions = self
+            .interactions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        interactions.retain(|i| i.request != request);
+        interactions.push(Interaction {
+            request,
+            // Only plain data, which always serializes.
+            response: serde_json::to_value(&response).expect("responses are serializable"),
+        });
+        self.save(&interactions)?;
+        Ok(response)
+    }
+
+    fn save(&self, interactions: &[Interaction]) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content =
+            serde_json::to_string_pretty(interactions).expect("interactions are serializable");
+        fs::write(&self.path, content + "\n")?;
+        Ok(())
+    }
+}
+
+impl<T: Translator> Translator for Recorder<T> {
+    fn usage_information(&self) -> Result<UsageInformation> {
+        self.call(json!({ "endpoint": "usage" }), |inner| {
+            inner.usage_information()
+        })
+    }
+
+    fn source_languages(&self) -> Result<LanguageList> {
+        self.call(json!({ "endpoint": "source_languages" }), |inner| {
+            inner.source_languages()
+        })
+    }
+
+    fn target_languages(&self) -> Result<LanguageList> {
+        self.call(json!({ "endpoint": "target_languages" }), |inner| {
+            inner.target_languages()
+        })
+    }
+
+    fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        let request = json!({
+            "endpoint": "translate",
+            "options": options,
+            "text_list": text_list,
+        });
+        self.call(request, |inner| inner.translate(options, text_list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::DeepL;
+
+    #[test]
+    fn record_and_replay() {
+        let path = std::env::temp_dir().join(format!(
+            "deepl-cassettes-{}/cassette.json",
+            std::process::id()
+        ));
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/v2/usage" => {
+                MockResponse::json(200, r#"{"character_count": 5, "character_limit": 10}"#)
+            }
+            _ => MockResponse::json(
+                200,
+                r#"{"translations": [{"detected_source_language": "EN", "text": "Hallo"}]}"#,
+            ),
+        });
+        let deepl = || DeepL::new("key".to_string(), false).with_base_url(server.url());
+        let texts = || TranslatableTextList::new("DE", vec!["Hello"]);
+
+        // First run records.
+        let recorder = Recorder::new(deepl(), &path).unwrap();
+        assert_eq!(recorder.translate(None, texts()).unwrap()[0].text, "Hallo");
+        assert_eq!(recorder.usage_information().unwrap().character_count, 5);
+        assert_eq!(server.requests().len(), 2);
+
+        // Later runs replay, also in replay-only mode.
+        let recorder = Recorder::new(deepl(), &path)
+            .unwrap()
+            .with_mode(RecordMode::Replay);
+        assert_eq!(recorder.translate(None, texts()).unwrap()[0].text, "Hallo");
+        assert_eq!(recorder.translate(None, texts()).unwrap()[0].text, "Hallo");
+        assert_eq!(recorder.usage_information().unwrap().character_limit, 10);
+        assert_eq!(server.requests().len(), 2);
+
+        // Different options are a different request.
+        let options = TranslationOptions::new().formality(crate::Formality::More);
+        let error = recorder.translate(Some(options), texts()).unwrap_err();
+        assert!(error.to_string().starts_with("no recorded response for"));
+
+        // Recording mode always asks the server.
+        let recorder = Recorder::new(deepl(), &path)
+            .unwrap()
+            .with_mode(RecordMode::Record);
+        recorder.translate(None, texts()).unwrap();
+        assert_eq!(server.requests().len(), 3);
+        let cassette: Vec<Interaction> = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(cassette.len(), 2);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,319 @@
+//! Versioned JSON representation of results, for consumption by external tools.
+//!
+//! The `deepl` command line client prints these documents with `--format json`. Each [Document]
+//! is a JSON object with the fields
+//!
+//! - `schema_version`: the [SCHEMA_VERSION] the document conforms to,
+//! - `type`: one of `translation`, `usage`, `languages` or `locale_report`, which determines the
+//!   remaining fields as described in [Body],
+//! - `warnings`: a list of human readable warnings, usually empty.
+//!
+//! For example:
+//!
+//! ```json
+//! {"schema_version":1,"type":"translation","target_language":"DE","source_language":null,
+//!  "translations":[{"index":0,"source":"Hello","text":"Hallo","detected_source_language":"EN"}],
+//!  "warnings":[]}
+//! ```
+//!
+//! # Stability
+//!
+//! Within one schema version, fields are only ever added, never removed, renamed or changed in
+//! meaning. Consumers should therefore ignore unknown fields. Optional fields that are absent
+//! in a result are omitted or `null` as documented per field. Any incompatible change increments
+//! [SCHEMA_VERSION].
+
+use crate::{Error, LanguageInformation, Result, TranslatedText, UsageInformation};
+use serde::{Deserialize, Serialize};
+
+/// Version of the JSON schema produced by this crate.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A versioned result document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    /// The schema version of the document.
+    pub schema_version: u32,
+    /// The actual result.
+    #[serde(flatten)]
+    pub body: Body,
+    /// Human readable warnings that occurred while producing the result.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+impl Document {
+    /// Wrap `body` in a document of the current [SCHEMA_VERSION].
+    pub fn new(body: Body) -> Document {
+        Document {
+            schema_version: SCHEMA_VERSION,
+            body,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Add a warning to the document.
+    pub fn with_warning(mut self, warning: impl Into<String>) -> Document {
+        self.warnings.push(warning.into());
+        self
+    }
+
+    /// Serialize the document as a single line of JSON.
+    pub fn to_json(&self) -> String {
+        // Only strings, numbers and flat structures, which always serialize.
+        serde_json::to_string(self).expect("documents are serializable")
+    }
+
+    /// Parse a document, failing for documents of a newer, incompatible schema version.
+    pub fn from_json(json: &str) -> Result<Document> {
+        let document: Document =
+            serde_json::from_str(json).map_err(|_| Error::DeserializationError)?;
+        match document.schema_version {
+            SCHEMA_VERSION => Ok(document),
+            _ => Err(Error::DeserializationError),
+        }
+    }
+}
+
+/// The different kinds of results, tagged by the `type` field.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Body {
+    /// The result of a translation.
+    Translation(TranslationResult),
+    /// Account usage and limits.
+    Usage(UsageResult),
+    /// Available source and target languages.
+    Languages(LanguagesResult),
+    /// Completeness of localization files.
+    LocaleReport(LocaleReportResult),
+}
+
+/// Type `translation`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TranslationResult {
+    /// The requested target language.
+    pub target_language: String,
+    /// The requested source language, or `null` if it was detected.
+    pub source_language: Option<String>,
+    /// One record per source text, ordered by `index`.
+    pub translations: Vec<TranslationRecord>,
+}
+
+/// One translated text of a [TranslationResult].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TranslationRecord {
+    /// Position of the source text in the request.
+    pub index: usize,
+    /// The source text, or `null` if it is not included.
+    pub source: Option<String>,
+    /// The translated text.
+    pub text: String,
+    /// The requested or detected source language.
+    pub detected_source_language: String,
+    /// Characters billed for this text. Omitted if unknown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billed_characters: Option<u64>,
+}
+
+impl TranslationRecord {
+    /// Build the record for the translation of the text at `index`.
+    pub fn new(
+        index: usize,
+        source: Option<String>,
+        translation: TranslatedText,
+    ) -> TranslationRecord {
+        TranslationRecord {
+            index,
+            source,
+            text: translation.text,
+            detected_source_language: translation.detected_source_language,
+            billed_characters: translation.billed_characters,
+        }
+    }
+}
+
+/// Type `usage`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UsageResult {
+    /// Characters translated in the current billing period.
+    pub character_count: u64,
+    /// Characters that can be translated per billing period.
+    pub character_limit: u64,
+    /// Documents translated in the current billing period. Omitted if not reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_count: Option<u64>,
+    /// Documents that can be translated per billing period. Omitted if not reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_limit: Option<u64>,
+}
+
+impl From<&UsageInformation> for UsageResult {
+    fn from(usage: &UsageInformation) -> UsageResult {
+        UsageResult {
+            character_count: usage.character_count,
+            character_limit: usage.character_limit,
+            document_count: usage.document_count,
+            document_limit: usage.document_limit,
+        }
+    }
+}
+
+/// Type `languages`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LanguagesResult {
+    /// Available source languages.
+    pub source: Vec<LanguageRecord>,
+    /// Available target languages.
+    pub target: Vec<LanguageRecord>,
+}
+
+/// One language of a [LanguagesResult].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LanguageRecord {
+    /// Language code, e. g. `EN-US`.
+    pub language: String,
+    /// English name of the language.
+    pub name: String,
+    /// Whether formality is supported. Omitted if unknown, which is always the case for source
+    /// languages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_formality: Option<bool>,
+}
+
+impl From<&LanguageInformation> for LanguageRecord {
+    fn from(language: &LanguageInformation) -> LanguageRecord {
+        LanguageRecord {
+            language: language.language.clone(),
+            name: language.name.clone(),
+            supports_formality: language.supports_formality,
+        }
+    }
+}
+
+/// Type `locale_report`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LocaleReportResult {
+    /// The locale all others are compared against.
+    pub source_locale: String,
+    /// One record per target locale, ordered by locale.
+    pub locales: Vec<LocaleRecord>,
+}
+
+/// Completeness of one target locale in a [LocaleReportResult].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LocaleRecord {
+    /// The target locale.
+    pub locale: String,
+    /// Number of keys of the source locale.
+    pub total: usize,
+    /// Percentage of source keys present in the target locale.
+    pub completeness: f64,
+    /// Keys of the source locale missing in the target locale.
+    pub missing: Vec<String>,
+    /// Keys of the target locale missing in the source locale.
+    pub extra: Vec<String>,
+    /// Keys whose message is identical to the source locale.
+    pub untranslated: Vec<String>,
+    /// Keys whose source message changed since the translations were last updated. Only present
+    /// if this was checked against a snapshot of the source messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outdated: Option<Vec<String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translation_schema() {
+        let translation = TranslatedText {
+            detected_source_language: "EN".to_string(),
+            text: "Hallo".to_string(),
+            billed_characters: None,
+            model_type_used: None,
+        };
+        let document = Document::new(Body::Translation(TranslationResult {
+            target_language: "DE".to_string(),
+            source_language: None,
+            translations: vec![TranslationRecord::new(
+                0,
+                Some("Hello".to_string()),
+                translation,
+            )],
+        }));
+        let json = r#"{"schema_version":1,"type":"translation","target_language":"DE","source_language":null,"translations":[{"index":0,"source":"Hello","text":"Hallo","detected_source_language":"EN"}],"warnings":[]}"#;
+        assert_eq!(document.to_json(), json);
+        assert_eq!(Document::from_json(json).unwrap(), document);
+    }
+
+    #[test]
+    fn usage_and_languages_schema() {
+        let document = Document::new(Body::Usage(UsageResult {
+            character_count: 10,
+            character_limit: 500000,
+            document_count: None,
+            document_limit: None,
+        }))
+        .with_warning("almost exhausted");
+        assert_eq!(
+            document.to_json(),
+            r#"{"schema_version":1,"type":"usage","character_count":10,"character_limit":500000,"warnings":["almost exhausted"]}"#
+        );
+
+        let document = Document::new(Body::Languages(LanguagesResult {
+            source: vec![LanguageRecord {
+                language: "DE".to_string(),
+                name: "German".to_string(),
+                supports_formality: None,
+            }],
+            target: vec![LanguageRecord {
+                language: "DE".to_string(),
+                name: "German".to_string(),
+                supports_formality: Some(true),
+            }],
+        }));
+        assert_eq!(
+            document.to_json(),
+            r#"{"schema_version":1,"type":"languages","source":[{"language":"DE","name":"German"}],"target":[{"language":"DE","name":"German","supports_formality":true}],"warnings":[]}"#
+        );
+    }
+
+    #[test]
+    fn locale_report_schema() {
+        let document = Document::new(Body::LocaleReport(LocaleReportResult {
+            source_locale: "en".to_string(),
+            locales: vec![LocaleRecord {
+                locale: "de".to_string(),
+                total: 2,
+                completeness: 50.0,
+                missing: vec!["bye".to_string()],
+                extra: vec![],
+                untranslated: vec![],
+                outdated: None,
+            }],
+        }));
+        assert_eq!(
+            document.to_json(),
+            r#"{"schema_version":1,"type":"locale_report","source_locale":"en","locales":[{"locale":"de","total":2,"completeness":50.0,"missing":["bye"],"extra":[],"untranslated":[]}],"warnings":[]}"#
+        );
+    }
+
+    #[test]
+    fn compatibility() {
+        // Unknown fields are ignored, missing warnings default to none.
+        let document = Document::from_json(
+            r#"{"schema_version":1,"type":"usage","character_count":1,"character_limit":2,"new_field":true}"#,
+        )
+        .unwrap();
+        assert!(document.warnings.is_empty());
+        assert!(matches!(document.body, Body::Usage(_)));
+
+        // Other versions are rejected.
+        assert!(Document::from_json(
+            r#"{"schema_version":2,"type":"usage","character_count":1,"character_limit":2}"#
+        )
+        .is_err());
+    }
+}
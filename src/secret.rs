@@ -0,0 +1,53 @@
+//! Protection of the API key against accidental disclosure.
+
+use std::fmt;
+use zeroize::Zeroize;
+
+/// A string that is not revealed by its [Debug] output and is wiped from memory when dropped,
+/// used for the API key.
+///
+/// ```rust
+/// use deepl_api::secret::SecretString;
+///
+/// let key = SecretString::from("0123-abcd:fx");
+/// assert_eq!(format!("{:?}", key), "SecretString(\"[REDACTED]\")");
+/// assert_eq!(key.expose_secret(), "0123-abcd:fx");
+/// ```
+#[derive(Clone, Default, PartialEq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Wrap `secret`.
+    pub fn new(secret: String) -> SecretString {
+        SecretString(secret)
+    }
+
+    /// The wrapped secret. Take care not to log or display it.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SecretString").field(&"[REDACTED]").finish()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> SecretString {
+        SecretString::new(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> SecretString {
+        SecretString::new(secret.to_string())
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
@@ -0,0 +1,188 @@
+//! A [tower](https://docs.rs/tower) service for translations, to compose the client with tower
+//! layers such as rate limits, timeouts or load shedding. Only available with the `tower` feature.
+//!
+//! The client is blocking, so [TranslationService] runs each translation on a thread of its own
+//! and returns a future that completes once the thread is done. The async executor is never
+//! blocked, and no particular async runtime is required.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use deepl_api::service::{TranslateRequest, TranslationService};
+//! use deepl_api::*;
+//! use tower_service::Service;
+//!
+//! # async fn example() -> Result<()> {
+//! let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap(), false);
+//! let mut service = TranslationService::new(deepl);
+//!
+//! let texts = TranslatableTextList::new(Language::De, vec!["Hello"]);
+//! let translated = service.call(TranslateRequest::new(texts)).await?;
+//! assert_eq!(translated[0].text, "Hallo");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{DeepL, Error, Result, TranslatableTextList, TranslatedText, TranslationOptions};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// The request of a [TranslationService]: the arguments of [DeepL::translate].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TranslateRequest {
+    /// Translation flags, see [TranslationOptions].
+    pub options: Option<TranslationOptions>,
+    /// The texts to translate.
+    pub text_list: TranslatableTextList,
+}
+
+impl TranslateRequest {
+    /// Translate `text_list` with the default options.
+    pub fn new(text_list: TranslatableTextList) -> TranslateRequest {
+        TranslateRequest {
+            options: None,
+            text_list,
+        }
+    }
+
+    /// Translate with `options`.
+    pub fn with_options(mut self, options: TranslationOptions) -> TranslateRequest {
+        self.options = Some(options);
+        self
+    }
+}
+
+/// A `tower::Service<TranslateRequest>` that translates with [DeepL::translate], see the
+/// [module documentation](self). Clones share the client.
+#[derive(Clone)]
+pub struct TranslationService {
+    deepl: Arc<DeepL>,
+}
+
+impl TranslationService {
+    /// Create a service that translates with `deepl`.
+    pub fn new(deepl: DeepL) -> TranslationService {
+        TranslationService {
+            deepl: Arc::new(deepl),
+        }
+    }
+}
+
+impl From<Arc<DeepL>> for TranslationService {
+    fn from(deepl: Arc<DeepL>) -> TranslationService {
+        TranslationService { deepl }
+    }
+}
+
+impl tower_service::Service<TranslateRequest> for TranslationService {
+    type Response = Vec<TranslatedText>;
+    type Error = Error;
+    type Future = TranslateFuture;
+
+    /// The service is always ready; limit the concurrency with a tower layer if needed.
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: TranslateRequest) -> TranslateFuture {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let deepl = self.deepl.clone();
+        let thread_shared = shared.clone();
+        std::thread::spawn(move || {
+            let result = deepl.translate(request.options, request.text_list);
+            let mut shared = thread_shared
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            shared.result = Some(result);
+            if let Some(waker) = shared.waker.take() {
+                waker.wake();
+            }
+        });
+        TranslateFuture { shared }
+    }
+}
+
+/// State shared between a [TranslateFuture] and the thread doing the translation.
+#[derive(Default)]
+struct Shared {
+    result: Option<Result<Vec<TranslatedText>>>,
+    waker: Option<Waker>,
+}
+
+/// The future returned by [TranslationService], which completes with the result of the
+/// translation. Dropping it does not stop the translation.
+pub struct TranslateFuture {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Future for TranslateFuture {
+    type Output = Result<Vec<TranslatedText>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self
+            .shared
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::Language;
+    use std::task::Wake;
+    use std::thread::Thread;
+    use tower_service::Service;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Minimal executor for a single future.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn translation_service() {
+        let server = MockServer::with_translator(crate::mock::MockDeepL::new().with_translation(
+            Language::De,
+            "Hello",
+            "Hallo",
+        ));
+        let mut service = TranslationService::new(server.client());
+
+        let texts = TranslatableTextList::new(Language::De, vec!["Hello"]);
+        let translated = block_on(service.call(TranslateRequest::new(texts))).unwrap();
+        assert_eq!(translated[0].text, "Hallo");
+
+        let texts = TranslatableTextList::new("XX", vec!["Hello"]);
+        let result = block_on(service.clone().call(TranslateRequest::new(texts)));
+        assert!(matches!(
+            result,
+            Err(Error::ServerError { status: 400, .. })
+        ));
+    }
+}
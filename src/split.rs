@@ -0,0 +1,130 @@
+//! Splitting of oversized texts at sentence boundaries, see [DeepL::with_text_splitting](crate::DeepL::with_text_splitting).
+
+/// Characters that end a sentence when followed by whitespace.
+const TERMINATORS: &[char] = &['.', '!', '?', '。', '！', '？'];
+/// Characters that may follow a terminator before the whitespace, like in `"Stop!" he said.`
+const CLOSING: &[char] = &['"', '\'', ')', ']', '»', '”', '’'];
+
+/// Splits `text` into consecutive pieces of at most `max_len` bytes. Pieces end at sentence
+/// boundaries where possible, otherwise at whitespace, and only as a last resort within a word.
+///
+/// Concatenating the pieces yields the original text.
+pub(crate) fn split_text(text: &str, max_len: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let (mut start, mut end) = (0, 0);
+    for sentence in sentences(text) {
+        if end > start && end - start + sentence.len() > max_len {
+            pieces.push(&text[start..end]);
+            start = end;
+        }
+        end += sentence.len();
+        while end - start > max_len {
+            let cut = hard_cut(&text[start..end], max_len);
+            pieces.push(&text[start..start + cut]);
+            start += cut;
+        }
+    }
+    if end > start {
+        pieces.push(&text[start..end]);
+    }
+    pieces
+}
+
+/// Splits `text` into sentences, each including the whitespace that follows it.
+fn sentences(text: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum State {
+        Within,
+        Terminated,
+        Gap,
+    }
+
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut state = State::Within;
+    for (i, c) in text.char_indices() {
+        if c == '\n' || (c.is_whitespace() && state == State::Terminated) {
+            state = State::Gap;
+            continue;
+        }
+        if c.is_whitespace() {
+            continue;
+        }
+        if state == State::Gap {
+            sentences.push(&text[start..i]);
+            start = i;
+        }
+        state = match TERMINATORS.contains(&c)
+            || (state == State::Terminated && CLOSING.contains(&c))
+        {
+            true => State::Terminated,
+            false => State::Within,
+        };
+    }
+    if start < text.len() {
+        sentences.push(&text[start..]);
+    }
+    sentences
+}
+
+/// Length of the first piece when `text` has to be split within a sentence. Always at least
+/// one character, to guarantee progress.
+fn hard_cut(text: &str, max_len: usize) -> usize {
+    let mut boundary = max_len.min(text.len());
+    while !text.is_char_boundary(boundary) {
+        boundary -= 1;
+    }
+    let whitespace = text[..boundary]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map(|(i, c)| i + c.len_utf8());
+    match whitespace {
+        Some(cut) if cut > 0 => cut,
+        _ if boundary > 0 => boundary,
+        _ => text.chars().next().map_or(text.len(), char::len_utf8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_text() {
+        let text = "Hello world. How are you?\n\nI am fine, thanks! \"Great.\" Version 1.5 is out.";
+        assert_eq!(
+            sentences(text),
+            vec![
+                "Hello world. ",
+                "How are you?\n\n",
+                "I am fine, thanks! ",
+                "\"Great.\" ",
+                "Version 1.5 is out."
+            ]
+        );
+
+        let pieces = super::split_text(text, 30);
+        assert_eq!(
+            pieces,
+            vec![
+                "Hello world. How are you?\n\n",
+                "I am fine, thanks! \"Great.\" ",
+                "Version 1.5 is out."
+            ]
+        );
+
+        // Sentences that are too long are split at whitespace, or within words.
+        assert_eq!(
+            super::split_text("One two three four.", 9),
+            vec!["One two ", "three ", "four."]
+        );
+        assert_eq!(super::split_text("Grüße", 3), vec!["Gr", "ü", "ße"]);
+        assert_eq!(super::split_text("ü", 1), vec!["ü"]);
+        assert_eq!(super::split_text("", 10), Vec::<&str>::new());
+
+        for max_len in 1..40 {
+            assert_eq!(super::split_text(text, max_len).concat(), text);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Statistics about the languages of a translated corpus.
+//!
+//! [CorpusStatistics] aggregates the results of one or more [translate](crate::DeepL::translate)
+//! calls and counts texts and characters per detected source language and per target language.
+//! The result can be exported as JSON or CSV.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::stats::CorpusStatistics;
+//! use deepl_api::TranslatedText;
+//!
+//! let translations: Vec<TranslatedText> = serde_json::from_str(
+//!     r#"[{"detected_source_language": "DE", "text": "yes"},
+//!         {"detected_source_language": "FR", "text": "no"}]"#,
+//! )
+//! .unwrap();
+//!
+//! let mut statistics = CorpusStatistics::new();
+//! statistics.add("EN-US", &["ja", "non"], &translations);
+//! assert_eq!(statistics.total.texts, 2);
+//! assert_eq!(statistics.by_source_language["FR"].source_characters, 3);
+//! assert_eq!(statistics.by_target_language["EN-US"].translated_characters, 5);
+//! ```
+
+use crate::{billable_characters, Language, TranslatedText};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Text and character counts of a part of the corpus.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct LanguageStatistics {
+    /// Number of translated texts.
+    pub texts: u64,
+    /// Number of characters of the source texts.
+    pub source_characters: u64,
+    /// Number of characters of the translated texts.
+    pub translated_characters: u64,
+}
+
+impl LanguageStatistics {
+    fn add(&mut self, source: &str, translation: &str) {
+        self.texts += 1;
+        self.source_characters += billable_characters(source);
+        self.translated_characters += billable_characters(translation);
+    }
+}
+
+/// Aggregated statistics of a translated corpus.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CorpusStatistics {
+    /// Statistics per source language, as detected by DeepL.
+    pub by_source_language: BTreeMap<String, LanguageStatistics>,
+    /// Statistics per target language.
+    pub by_target_language: BTreeMap<String, LanguageStatistics>,
+    /// Statistics of the whole corpus.
+    pub total: LanguageStatistics,
+}
+
+impl CorpusStatistics {
+    /// Create empty statistics.
+    pub fn new() -> CorpusStatistics {
+        CorpusStatistics::default()
+    }
+
+    /// Add the result of one translation into `target_language`. `sources` holds the texts that were
+    /// sent and `translations` the results, in the same order.
+    pub fn add<S: AsRef<str>>(
+        &mut self,
+        target_language: impl Into<Language>,
+        sources: &[S],
+        translations: &[TranslatedText],
+    ) {
+        let target_language = target_language.into().to_string();
+        for (source, translation) in sources.iter().zip(translations) {
+            let (source, text) = (source.as_ref(), translation.text.as_str());
+            self.by_source_language
+                .entry(Language::from(&translation.detected_source_language).to_string())
+                .or_default()
+                .add(source, text);
+            self.by_target_language
+                .entry(target_language.clone())
+                .or_default()
+                .add(source, text);
+            self.total.add(source, text);
+        }
+    }
+
+    /// Export the statistics as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        // Maps with string keys and plain numbers always serialize.
+        serde_json::to_string_pretty(self).expect("statistics are serializable")
+    }
+
+    /// Export the statistics as CSV with the columns
+    /// `scope,language,texts,source_characters,translated_characters`.
+    ///
+    /// `scope` is one of `source`, `target` or `total`; the language is empty for the total.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "scope,language,texts,source_characters,translated_characters\n".to_string();
+        let rows = self
+            .by_source_language
+            .iter()
+            .map(|(language, stats)| ("source", language.as_str(), stats))
+            .chain(
+                self.by_target_language
+                    .iter()
+                    .map(|(language, stats)| ("target", language.as_str(), stats)),
+            )
+            .chain(std::iter::once(("total", "", &self.total)));
+        for (scope, language, stats) in rows {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                scope, language, stats.texts, stats.source_characters, stats.translated_characters
+            ));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translated(detected_source_language: &str, text: &str) -> TranslatedText {
+        TranslatedText {
+            detected_source_language: detected_source_language.to_string(),
+            text: text.to_string(),
+            billed_characters: None,
+            model_type_used: None,
+        }
+    }
+
+    #[test]
+    fn corpus_statistics() {
+        let mut statistics = CorpusStatistics::new();
+        statistics.add(
+            "EN-US",
+            &["ja", "Grüße"],
+            &[translated("DE", "yes"), translated("de", "Greetings")],
+        );
+        statistics.add(Language::Fr, &["Hallo"], &[translated("DE", "Salut")]);
+
+        assert_eq!(
+            statistics.by_source_language["DE"],
+            LanguageStatistics {
+                texts: 3,
+                source_characters: 12,
+                translated_characters: 17,
+            }
+        );
+        assert_eq!(statistics.by_target_language["FR"].texts, 1);
+        assert_eq!(statistics.total.texts, 3);
+
+        assert_eq!(
+            statistics.to_csv(),
+            "scope,language,texts,source_characters,translated_characters\n\
+             source,DE,3,12,17\n\
+             target,EN-US,2,7,12\n\
+             target,FR,1,5,5\n\
+             total,,3,12,17\n"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&statistics.to_json()).unwrap();
+        assert_eq!(json["by_target_language"]["EN-US"]["source_characters"], 7);
+        assert_eq!(json["total"]["texts"], 3);
+    }
+}
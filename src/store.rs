@@ -0,0 +1,489 @@
+//! Pluggable key-value persistence for the crate's stateful helpers.
+//!
+//! All components that need to keep data around (e. g. caches, ledgers or queues) persist it
+//! through the [Store] trait, so that a deployment can back all of them with the same storage.
+//! Data is organized in namespaces, one per component, which hold binary values under string keys.
+//!
+//! Bundled backends:
+//!
+//! - [MemoryStore]: keeps everything in memory, e. g. for tests or short-lived processes.
+//! - [FileStore]: one file per entry below a directory.
+//! - `SqliteStore`: a single SQLite database file (requires the `sqlite` feature).
+//!
+//! Custom backends can be plugged in by implementing [Store] and reporting failures as
+//! [StorageError](crate::Error::StorageError).
+//!
+//! With the `encryption` feature, any backend can be wrapped in an `EncryptedStore` to
+//! protect the stored values (e. g. cached translations of confidential documents) at rest.
+
+use crate::{Error, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// A namespaced key-value store.
+pub trait Store: Send + Sync {
+    /// Fetch the value stored under `key`, if any.
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Store `value` under `key`, replacing any previous value.
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<()>;
+
+    /// Remove the value stored under `key`. Does nothing if there is none.
+    fn delete(&self, namespace: &str, key: &str) -> Result<()>;
+
+    /// List all entries of the namespace whose key starts with `prefix`, ordered by key.
+    fn scan(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, Vec<u8>)>>;
+}
+
+/// A [Store] that keeps all data in memory.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    entries: Mutex<BTreeMap<(String, String), Vec<u8>>>,
+}
+
+impl MemoryStore {
+    /// Create an empty store.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl Store for MemoryStore {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .get(&(namespace.to_string(), key.to_string()))
+            .cloned())
+    }
+
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert((namespace.to_string(), key.to_string()), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(&(namespace.to_string(), key.to_string()));
+        Ok(())
+    }
+
+    fn scan(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .range((namespace.to_string(), prefix.to_string())..)
+            .take_while(|((ns, key), _)| ns == namespace && key.starts_with(prefix))
+            .map(|((_, key), value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+/// The maximum length in bytes of the keys and namespaces of a [FileStore].
+pub const MAX_FILE_STORE_KEY_LENGTH: usize = 100;
+
+/// Numbers the temporary files of [FileStore] writes within this process.
+static TEMP_FILE_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// A [Store] that keeps each entry in a separate file, below a subdirectory per namespace.
+///
+/// Keys are hex-encoded to form valid file names, so any string of up to
+/// [MAX_FILE_STORE_KEY_LENGTH] bytes can be used as key or namespace. Longer ones would exceed
+/// the file name limit of common filesystems and are refused with a
+/// [StorageError](crate::Error::StorageError).
+///
+/// Writes go to a temporary file first, so that entries are never left half-written.
+#[derive(Debug)]
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    /// Open the store in directory `root`, which will be created if necessary.
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<FileStore> {
+        fs::create_dir_all(root.as_ref())?;
+        Ok(FileStore {
+            root: root.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path(&self, namespace: &str, key: &str) -> Result<PathBuf> {
+        for name in [namespace, key] {
+            if name.len() > MAX_FILE_STORE_KEY_LENGTH {
+                return Err(Error::StorageError(format!(
+                    "key or namespace of {} bytes exceeds the maximum of {} bytes of FileStore",
+                    name.len(),
+                    MAX_FILE_STORE_KEY_LENGTH
+                )));
+            }
+        }
+        Ok(self.root.join(hex_encode(namespace)).join(hex_encode(key)))
+    }
+}
+
+impl Store for FileStore {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.path(namespace, key)?) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<()> {
+        let path = self.path(namespace, key)?;
+        fs::create_dir_all(self.root.join(hex_encode(namespace)))?;
+        // A temporary file per write, so that concurrent writers of the same key don't interfere.
+        let temp_path = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            TEMP_FILE_SEQUENCE.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::write(&temp_path, value)?;
+        fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<()> {
+        match fs::remove_file(self.path(namespace, key)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn scan(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let dir = self.root.join(hex_encode(namespace));
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut result = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            // Skips temporary files and anything else that was not written by us.
+            let key = match entry.file_name().to_str().and_then(hex_decode) {
+                Some(key) => key,
+                None => continue,
+            };
+            if key.starts_with(prefix) {
+                result.push((key, fs::read(entry.path())?));
+            }
+        }
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(result)
+    }
+}
+
+fn hex_encode(text: &str) -> String {
+    text.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_decode(hex: &str) -> Option<String> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// A [Store] backed by a single SQLite database file.
+#[cfg(feature = "sqlite")]
+pub struct SqliteStore {
+    connection: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    /// Open (or create) the database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SqliteStore> {
+        SqliteStore::from_connection(rusqlite::Connection::open(path).map_err(storage_error)?)
+    }
+
+    /// Create a store in an in-memory database.
+    pub fn open_in_memory() -> Result<SqliteStore> {
+        SqliteStore::from_connection(rusqlite::Connection::open_in_memory().map_err(storage_error)?)
+    }
+
+    fn from_connection(connection: rusqlite::Connection) -> Result<SqliteStore> {
+        connection
+            .execute(
+                "CREATE TABLE IF NOT EXISTS deepl_store (
+                    namespace TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    value BLOB NOT NULL,
+                    PRIMARY KEY (namespace, key)
+                )",
+                [],
+            )
+            .map_err(storage_error)?;
+        Ok(SqliteStore {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn storage_error(e: rusqlite::Error) -> crate::Error {
+    crate::Error::StorageError(e.to_string())
+}
+
+#[cfg(feature = "sqlite")]
+impl Store for SqliteStore {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        use rusqlite::OptionalExtension;
+        let connection = self.connection.lock().unwrap();
+        connection
+            .query_row(
+                "SELECT value FROM deepl_store WHERE namespace = ?1 AND key = ?2",
+                [namespace, key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(storage_error)
+    }
+
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<()> {
+        let connection = self.connection.lock().unwrap();
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO deepl_store (namespace, key, value) VALUES (?1, ?2, ?3)",
+                rusqlite::params![namespace, key, value],
+            )
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<()> {
+        let connection = self.connection.lock().unwrap();
+        connection
+            .execute(
+                "DELETE FROM deepl_store WHERE namespace = ?1 AND key = ?2",
+                [namespace, key],
+            )
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    fn scan(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection
+            .prepare("SELECT key, value FROM deepl_store WHERE namespace = ?1 ORDER BY key")
+            .map_err(storage_error)?;
+        let rows = statement
+            .query_map([namespace], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(storage_error)?;
+        let mut result = Vec::new();
+        for row in rows {
+            let (key, value): (String, Vec<u8>) = row.map_err(storage_error)?;
+            if key.starts_with(prefix) {
+                result.push((key, value));
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// A [Store] wrapper that encrypts all values with XChaCha20-Poly1305 before handing them to
+/// the inner store, and decrypts them when reading.
+///
+/// Only values are encrypted; namespaces and keys are stored as they are, so they must not
+/// contain confidential data. Every value is bound to its namespace and key, so values cannot
+/// be swapped between entries unnoticed. Reading a value that was tampered with or encrypted
+/// with a different key fails with [StorageError](crate::Error::StorageError).
+#[cfg(feature = "encryption")]
+pub struct EncryptedStore<S: Store> {
+    inner: S,
+    cipher: chacha20poly1305::XChaCha20Poly1305,
+}
+
+#[cfg(feature = "encryption")]
+impl<S: Store> EncryptedStore<S> {
+    /// Wrap `inner`, encrypting with the given 256 bit key.
+    pub fn new(inner: S, key: &[u8; 32]) -> EncryptedStore<S> {
+        use chacha20poly1305::KeyInit;
+        EncryptedStore {
+            inner,
+            cipher: chacha20poly1305::XChaCha20Poly1305::new(key.into()),
+        }
+    }
+
+    fn encrypt(&self, namespace: &str, key: &str, value: &[u8]) -> Result<Vec<u8>> {
+        use chacha20poly1305::aead::{Aead, AeadCore, OsRng, Payload};
+        let nonce = chacha20poly1305::XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let aad = associated_data(namespace, key);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: value,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| crate::Error::StorageError("encryption failed".to_string()))?;
+        let mut result = nonce.to_vec();
+        result.extend(ciphertext);
+        Ok(result)
+    }
+
+    fn decrypt(&self, namespace: &str, key: &str, value: &[u8]) -> Result<Vec<u8>> {
+        use chacha20poly1305::aead::{Aead, Payload};
+        const NONCE_SIZE: usize = 24;
+        let error = || crate::Error::StorageError(format!("cannot decrypt entry '{}'", key));
+        if value.len() < NONCE_SIZE {
+            return Err(error());
+        }
+        let (nonce, ciphertext) = value.split_at(NONCE_SIZE);
+        let aad = associated_data(namespace, key);
+        self.cipher
+            .decrypt(
+                nonce.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| error())
+    }
+}
+
+#[cfg(feature = "encryption")]
+fn associated_data(namespace: &str, key: &str) -> Vec<u8> {
+    let mut aad = namespace.as_bytes().to_vec();
+    aad.push(0);
+    aad.extend(key.as_bytes());
+    aad
+}
+
+#[cfg(feature = "encryption")]
+impl<S: Store> Store for EncryptedStore<S> {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        match self.inner.get(namespace, key)? {
+            Some(value) => Ok(Some(self.decrypt(namespace, key, &value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<()> {
+        let value = self.encrypt(namespace, key, value)?;
+        self.inner.put(namespace, key, &value)
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<()> {
+        self.inner.delete(namespace, key)
+    }
+
+    fn scan(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        self.inner
+            .scan(namespace, prefix)?
+            .into_iter()
+            .map(|(key, value)| {
+                let value = self.decrypt(namespace, &key, &value)?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_store(store: &dyn Store) {
+        assert_eq!(store.get("cache", "a").unwrap(), None);
+
+        store.put("cache", "a/1", b"one").unwrap();
+        store.put("cache", "a/2", b"two").unwrap();
+        store.put("cache", "b/1", b"three").unwrap();
+        store.put("ledger", "a/1", b"other").unwrap();
+        store.put("cache", "a/1", b"ONE").unwrap();
+
+        assert_eq!(store.get("cache", "a/1").unwrap(), Some(b"ONE".to_vec()));
+        assert_eq!(store.get("ledger", "a/1").unwrap(), Some(b"other".to_vec()));
+        assert_eq!(
+            store.scan("cache", "a/").unwrap(),
+            vec![
+                ("a/1".to_string(), b"ONE".to_vec()),
+                ("a/2".to_string(), b"two".to_vec())
+            ]
+        );
+        assert_eq!(store.scan("cache", "").unwrap().len(), 3);
+        assert_eq!(store.scan("queue", "").unwrap(), vec![]);
+
+        store.delete("cache", "a/1").unwrap();
+        store.delete("cache", "nonexisting").unwrap();
+        assert_eq!(store.get("cache", "a/1").unwrap(), None);
+        assert_eq!(store.scan("cache", "a/").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn memory_store() {
+        check_store(&MemoryStore::new());
+    }
+
+    #[test]
+    fn file_store() {
+        let dir = std::env::temp_dir().join(format!("deepl-file-store-{}", std::process::id()));
+        let store = FileStore::open(&dir).unwrap();
+        check_store(&store);
+
+        let key = "k".repeat(MAX_FILE_STORE_KEY_LENGTH);
+        store.put("cache", &key, b"long").unwrap();
+        assert_eq!(store.get("cache", &key).unwrap(), Some(b"long".to_vec()));
+        match store.put("cache", &format!("{}k", key), b"too long") {
+            Err(Error::StorageError(message)) => assert!(message.contains("101 bytes")),
+            result => panic!("unexpected result {:?}", result),
+        }
+
+        // Concurrent writers of the same key each use their own temporary file.
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let store = &store;
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        store
+                            .put("race", "key", format!("{}", i).as_bytes())
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(store.scan("race", "").unwrap().len(), 1);
+        assert_eq!(
+            fs::read_dir(dir.join(hex_encode("race"))).unwrap().count(),
+            1
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_store() {
+        let store = EncryptedStore::new(MemoryStore::new(), &[7; 32]);
+        check_store(&store);
+
+        // Values are not stored in plain text.
+        store.put("cache", "secret", b"confidential").unwrap();
+        let raw = store.inner.get("cache", "secret").unwrap().unwrap();
+        assert!(!raw.windows(12).any(|w| w == b"confidential"));
+
+        // A different key cannot decrypt the data.
+        let other = EncryptedStore::new(store.inner, &[8; 32]);
+        assert!(matches!(
+            other.get("cache", "secret"),
+            Err(crate::Error::StorageError(_))
+        ));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_store() {
+        check_store(&SqliteStore::open_in_memory().unwrap());
+    }
+}
@@ -0,0 +1,512 @@
+//! Local HTTP servers for exercising the client without network access.
+//!
+//! [MockServer::deepl] starts a server that speaks the JSON contract of the DeepL API for
+//! `/v2/translate`, `/v2/usage` and `/v2/languages`, backed by a [MockDeepL]. Use
+//! [MockServer::client] to get a [DeepL] client that talks to it, and
+//! [MockServer::requests] to inspect what was sent. For full control over the responses, start
+//! a server with your own handler via [MockServer::start].
+//!
+//! Only available with the `testing` feature:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! deepl-api = { version = "0.2", features = ["testing"] }
+//! ```
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::mock::MockDeepL;
+//! use deepl_api::testing::MockServer;
+//! use deepl_api::*;
+//!
+//! let server = MockServer::with_translator(
+//!     MockDeepL::new().with_translation(Language::De, "Hello", "Hallo"),
+//! );
+//! let deepl = server.client();
+//!
+//! let texts = TranslatableTextList::new(Language::De, vec!["Hello"]);
+//! assert_eq!(deepl.translate(None, texts).unwrap()[0].text, "Hallo");
+//! assert_eq!(server.requests()[0].path, "/v2/translate?target_lang=DE&text=Hello");
+//! ```
+
+use crate::mock::MockDeepL;
+use crate::{
+    DeepL, Error, Formality, ModelType, SplitSentences, TagHandling, TranslatableTextList,
+    TranslationOptions, Translator,
+};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// API key of the clients returned by [MockServer::client].
+pub const MOCK_API_KEY: &str = "mock-key";
+
+/// A request as received by the [MockServer].
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    /// HTTP method, e. g. `POST`.
+    pub method: String,
+    /// Path including the query string.
+    pub path: String,
+    /// Header names are lowercased.
+    pub headers: Vec<(String, String)>,
+    /// The request body.
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// The value of the header `name`, which must be lowercase.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The decoded parameters of the query string and a form-encoded body, in order.
+    pub fn params(&self) -> Vec<(String, String)> {
+        let query = self.path.split_once('?').map(|(_, query)| query);
+        [query.unwrap_or_default(), &self.body]
+            .iter()
+            .flat_map(|params| form_urlencoded(params))
+            .collect()
+    }
+
+    /// All values of the parameter `name`.
+    pub fn param_values(&self, name: &str) -> Vec<String> {
+        self.params()
+            .into_iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, v)| v)
+            .collect()
+    }
+}
+
+/// A canned response of the [MockServer].
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response headers.
+    pub headers: Vec<(String, String)>,
+    /// The response body.
+    pub body: String,
+}
+
+impl MockResponse {
+    /// A response with a JSON `body`.
+    pub fn json(status: u16, body: &str) -> MockResponse {
+        MockResponse {
+            status,
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.into(),
+        }
+    }
+}
+
+type Handler = dyn Fn(&RecordedRequest) -> MockResponse + Send + Sync;
+
+/// Serves responses on a random local port and records all received requests. The server runs
+/// until the process exits.
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Start a server that answers all requests with `handler`.
+    ///
+    /// Panics if no local port can be bound.
+    pub fn start<F>(handler: F) -> MockServer
+    where
+        F: Fn(&RecordedRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind a local port");
+        let url = format!("http://{}", listener.local_addr().expect("local address"));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let recorded = recorded.clone();
+                let handler = handler.clone();
+                thread::spawn(move || serve(stream, &*handler, &recorded));
+            }
+        });
+
+        MockServer { url, requests }
+    }
+
+    /// Start a server that implements the DeepL API with a default [MockDeepL], which returns
+    /// all texts unchanged.
+    pub fn deepl() -> MockServer {
+        MockServer::with_translator(MockDeepL::new())
+    }
+
+    /// Start a server that implements the DeepL API on top of `translator`.
+    ///
+    /// Requests must be authorized like with the real API, otherwise they fail with status 403.
+    /// Errors of the translator are mapped to the status codes DeepL uses for them.
+    pub fn with_translator<T>(translator: T) -> MockServer
+    where
+        T: Translator + Send + Sync + 'static,
+    {
+        MockServer::start(move |request| handle_api_request(&translator, request))
+    }
+
+    /// Start a server that answers every request with the translations of its texts by
+    /// `translate`, e. g. a few [replacements](str::replace) of words. The detected source
+    /// language is always `EN`.
+    pub fn translating<F>(translate: F) -> MockServer
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        MockServer::start(move |request| {
+            let translations: Vec<_> = request
+                .param_values("text")
+                .iter()
+                .map(|text| json!({"detected_source_language": "EN", "text": translate(text)}))
+                .collect();
+            MockResponse::json(200, &json!({ "translations": translations }).to_string())
+        })
+    }
+
+    /// Base URL to be passed to [DeepL::with_base_url].
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A client for this server, authorized with [MOCK_API_KEY].
+    pub fn client(&self) -> DeepL {
+        DeepL::new(MOCK_API_KEY.to_string(), false).with_base_url(self.url())
+    }
+
+    /// All requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+fn handle_api_request(translator: &dyn Translator, request: &RecordedRequest) -> MockResponse {
+    let key = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("DeepL-Auth-Key "))
+        .map(String::from)
+        .or_else(|| request.param_values("auth_key").into_iter().next());
+    let authorized = key.is_some_and(|key| !key.is_empty());
+    if !authorized {
+        return error_response(403, "Forbidden");
+    }
+
+    let path = request.path.split('?').next().unwrap_or_default();
+    let result = match path {
+        "/v2/usage" => translator
+            .usage_information()
+            .map(|usage| serde_json::to_string(&usage)),
+        "/v2/languages" => {
+            let languages = match request.param_values("type").first().map(String::as_str) {
+                None | Some("source") => translator.source_languages(),
+                Some("target") => translator.target_languages(),
+                Some(_) => return error_response(400, "Value for 'type' not supported."),
+            };
+            languages.map(|languages| serde_json::to_string(&languages))
+        }
+        "/v2/translate" => {
+            let (options, text_list) = match translate_request(request) {
+                Ok(request) => request,
+                Err(message) => return error_response(400, &message),
+            };
+            translator
+                .translate(Some(options), text_list)
+                .map(|translations| serde_json::to_string(&json!({ "translations": translations })))
+        }
+        _ => return error_response(404, "Not found"),
+    };
+    match result {
+        Ok(body) => MockResponse::json(200, &body.expect("responses are serializable")),
+        Err(Error::AuthorizationError) => error_response(403, "Forbidden"),
+        Err(Error::QuotaExceeded) => error_response(456, "Quota exceeded"),
+        Err(Error::TooManyRequests { retry_after }) => {
+            let mut response = error_response(429, "Too many requests");
+            if let Some(retry_after) = retry_after {
+                response
+                    .headers
+                    .push(("Retry-After".into(), retry_after.as_secs().to_string()));
+            }
+            response
+        }
+        Err(Error::ServerError {
+            message, status, ..
+        }) => error_response(status, &message),
+        Err(error) => error_response(500, &error.to_string()),
+    }
+}
+
+fn translate_request(
+    request: &RecordedRequest,
+) -> std::result::Result<(TranslationOptions, TranslatableTextList), String> {
+    let param = |name: &str| request.param_values(name).into_iter().next();
+    let target_language =
+        param("target_lang").ok_or_else(|| "Parameter 'target_lang' not specified.".to_string())?;
+    let texts = request.param_values("text");
+    if texts.is_empty() {
+        return Err("Parameter 'text' not specified.".to_string());
+    }
+    let text_list = TranslatableTextList {
+        source_language: param("source_lang"),
+        target_language,
+        texts,
+    };
+
+    let unsupported = |name: &str| format!("Value for '{}' not supported.", name);
+    let mut options = TranslationOptions::new();
+    if let Some(value) = param("split_sentences") {
+        options = options.split_sentences(match value.as_str() {
+            "0" => SplitSentences::None,
+            "1" => SplitSentences::PunctuationAndNewlines,
+            "nonewlines" => SplitSentences::Punctuation,
+            _ => return Err(unsupported("split_sentences")),
+        });
+    }
+    if let Some(value) = param("preserve_formatting") {
+        options = options
+            .preserve_formatting(flag(&value).ok_or_else(|| unsupported("preserve_formatting"))?);
+    }
+    if let Some(value) = param("formality") {
+        options = options.formality(match value.as_str() {
+            "default" => Formality::Default,
+            "more" => Formality::More,
+            "less" => Formality::Less,
+            _ => return Err(unsupported("formality")),
+        });
+    }
+    if let Some(value) = param("model_type") {
+        options = options.model_type(match value.as_str() {
+            "latency_optimized" => ModelType::LatencyOptimized,
+            "quality_optimized" => ModelType::QualityOptimized,
+            "prefer_quality_optimized" => ModelType::PreferQualityOptimized,
+            _ => return Err(unsupported("model_type")),
+        });
+    }
+    if let Some(value) = param("show_billed_characters") {
+        options = options.show_billed_characters(
+            flag(&value).ok_or_else(|| unsupported("show_billed_characters"))?,
+        );
+    }
+    if let Some(value) = param("glossary_id") {
+        options = options.glossary(value);
+    }
+    if let Some(value) = param("tag_handling") {
+        options = options.tag_handling(match value.as_str() {
+            "html" => TagHandling::Html,
+            "xml" => TagHandling::Xml,
+            _ => return Err(unsupported("tag_handling")),
+        });
+    }
+    if let Some(value) = param("ignore_tags") {
+        options = options.ignore_tags(value.split(','));
+    }
+    Ok((options, text_list))
+}
+
+fn flag(value: &str) -> Option<bool> {
+    match value {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+fn error_response(status: u16, message: &str) -> MockResponse {
+    MockResponse::json(status, &json!({ "message": message }).to_string())
+}
+
+/// Decode `application/x-www-form-urlencoded` parameters.
+fn form_urlencoded(params: &str) -> Vec<(String, String)> {
+    params
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => match rest
+                .get(..2)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(decoded) => {
+                    bytes.push(decoded);
+                    rest = &rest[2..];
+                }
+                None => bytes.push(b'%'),
+            },
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn serve(stream: TcpStream, handler: &Handler, recorded: &Mutex<Vec<RecordedRequest>>) {
+    let mut reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(_) => return,
+    };
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+
+    let request = RecordedRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    let response = handler(&request);
+    recorded
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .push(request);
+
+    let mut out = format!("HTTP/1.1 {} Mock\r\n", response.status);
+    for (name, value) in &response.headers {
+        out.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    out.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.body.len(),
+        response.body
+    ));
+    let mut stream = stream;
+    let _ = stream.write_all(out.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Language, LanguageInformation};
+
+    #[test]
+    fn deepl_server() {
+        let server =
+            MockServer::with_translator(MockDeepL::new().with_usage(0, 100).with_translation(
+                Language::De,
+                "Hello, world & more",
+                "Hallo, Welt & mehr",
+            ));
+        let deepl = server.client();
+
+        let texts = TranslatableTextList::new(Language::De, vec!["Hello, world & more", "ja"])
+            .with_source_language(Language::En);
+        let options = TranslationOptions::new()
+            .formality(Formality::Less)
+            .show_billed_characters(true);
+        let translated = deepl.translate(Some(options), texts).unwrap();
+        assert_eq!(translated[0].text, "Hallo, Welt & mehr");
+        assert_eq!(translated[0].detected_source_language, "EN");
+        assert_eq!(translated[1].text, "ja");
+        assert_eq!(translated[1].billed_characters, Some(2));
+        let request = server
+            .requests()
+            .into_iter()
+            .find(|request| request.path.starts_with("/v2/translate"))
+            .unwrap();
+        assert_eq!(request.param_values("formality"), vec!["less"]);
+        assert_eq!(
+            request.param_values("text"),
+            vec!["Hello, world & more", "ja"]
+        );
+
+        assert_eq!(deepl.usage_information().unwrap().character_count, 21);
+        let target_languages: Vec<LanguageInformation> = deepl.target_languages().unwrap();
+        assert_eq!(target_languages[0].language, "DE");
+        assert_eq!(target_languages[0].supports_formality, Some(true));
+        assert_eq!(
+            deepl.source_languages().unwrap()[0].supports_formality,
+            None
+        );
+
+        // Errors map to the status codes of the real API.
+        let texts = TranslatableTextList::new("XX", vec!["Hello"]);
+        assert!(matches!(
+            deepl.translate(None, texts),
+            Err(Error::ServerError { status: 400, .. })
+        ));
+        let texts = TranslatableTextList::new(Language::Fr, vec!["a".repeat(100)]);
+        assert!(matches!(
+            deepl.translate(None, texts),
+            Err(Error::QuotaExceeded)
+        ));
+        let unauthorized = DeepL::new(String::new(), false).with_base_url(server.url());
+        assert!(matches!(
+            unauthorized.usage_information(),
+            Err(Error::AuthorizationError)
+        ));
+    }
+
+    #[test]
+    fn params() {
+        let request = RecordedRequest {
+            method: "POST".to_string(),
+            path: "/v2/translate?text=a+b%26c&text=%E6%97%A5%2&flag".to_string(),
+            headers: Vec::new(),
+            body: "target_lang=DE".to_string(),
+        };
+        assert_eq!(
+            request.params(),
+            vec![
+                ("text".to_string(), "a b&c".to_string()),
+                ("text".to_string(), "日%2".to_string()),
+                ("flag".to_string(), String::new()),
+                ("target_lang".to_string(), "DE".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Export of translations as [TMX 1.4](https://www.gala-global.org/tmx-14b) translation memory,
+//! e. g. to feed DeepL output into CAT tools.
+//!
+//! [TranslationMemory] accumulates the results of one or more [translate](crate::DeepL::translate)
+//! calls as segments of source and translated text, and serializes them to a TMX document with
+//! one translation unit per segment.
+//!
+//! # Example
+//!
+//! ```rust
+//! use deepl_api::tmx::TranslationMemory;
+//! use deepl_api::TranslatedText;
+//!
+//! let translations: Vec<TranslatedText> = serde_json::from_str(
+//!     r#"[{"detected_source_language": "DE", "text": "yes"}]"#,
+//! )
+//! .unwrap();
+//!
+//! let mut memory = TranslationMemory::new();
+//! memory.add("EN-US", &["ja"], &translations);
+//! let tmx = memory.to_tmx();
+//! assert!(tmx.contains(r#"<tuv xml:lang="de"><seg>ja</seg></tuv>"#));
+//! assert!(tmx.contains(r#"<tuv xml:lang="en-US"><seg>yes</seg></tuv>"#));
+//! ```
+
+use crate::{Language, TranslatedText};
+use std::fs;
+use std::path::Path;
+
+/// One source text with its translation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    /// The source language, as detected by DeepL.
+    pub source_language: Language,
+    /// The target language.
+    pub target_language: Language,
+    /// The source text.
+    pub source: String,
+    /// The translated text.
+    pub target: String,
+}
+
+/// A collection of translated segments that can be exported as TMX.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TranslationMemory {
+    segments: Vec<Segment>,
+}
+
+impl TranslationMemory {
+    /// Create an empty translation memory.
+    pub fn new() -> TranslationMemory {
+        TranslationMemory::default()
+    }
+
+    /// Add the result of one translation into `target_language`. `sources` holds the texts that
+    /// were sent and `translations` the results, in the same order.
+    pub fn add<S: AsRef<str>>(
+        &mut self,
+        target_language: impl Into<Language>,
+        sources: &[S],
+        translations: &[TranslatedText],
+    ) {
+        let target_language = target_language.into();
+        for (source, translation) in sources.iter().zip(translations) {
+            self.segments.push(Segment {
+                source_language: Language::from(&translation.detected_source_language),
+                target_language: target_language.clone(),
+                source: source.as_ref().to_string(),
+                target: translation.text.clone(),
+            });
+        }
+    }
+
+    /// Add a single segment.
+    pub fn add_segment(&mut self, segment: Segment) {
+        self.segments.push(segment);
+    }
+
+    /// The segments added so far, in order.
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Serialize the segments as TMX 1.4 document. The languages are written as RFC 3066 tags,
+    /// e. g. `en-US` for `EN-US`. The source language of the header is the common source language
+    /// of all segments, or `*all*` if they differ.
+    pub fn to_tmx(&self) -> String {
+        let mut source_languages = self.segments.iter().map(|s| &s.source_language);
+        let srclang = match source_languages.next() {
+            Some(first) if source_languages.all(|l| l == first) => language_tag(first),
+            _ => "*all*".to_string(),
+        };
+
+        let mut tmx = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        tmx.push_str("<!DOCTYPE tmx SYSTEM \"tmx14.dtd\">\n");
+        tmx.push_str("<tmx version=\"1.4\">\n");
+        tmx.push_str(&format!(
+            "  <header creationtool=\"{}\" creationtoolversion=\"{}\" segtype=\"block\" \
+             o-tmf=\"DeepL\" adminlang=\"en\" srclang=\"{}\" datatype=\"plaintext\"/>\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            escape(&srclang)
+        ));
+        tmx.push_str("  <body>\n");
+        for segment in &self.segments {
+            tmx.push_str("    <tu>\n");
+            for (language, text) in [
+                (&segment.source_language, &segment.source),
+                (&segment.target_language, &segment.target),
+            ] {
+                tmx.push_str(&format!(
+                    "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+                    escape(&language_tag(language)),
+                    escape(text)
+                ));
+            }
+            tmx.push_str("    </tu>\n");
+        }
+        tmx.push_str("  </body>\n</tmx>\n");
+        tmx
+    }
+
+    /// Write the [TMX document](TranslationMemory::to_tmx) to `path`.
+    pub fn write_to<P: AsRef<Path>>(&self, path: P) -> crate::Result<()> {
+        fs::write(path, self.to_tmx())?;
+        Ok(())
+    }
+}
+
+/// The RFC 3066 tag of a DeepL language code: the language in lower case, the region in upper
+/// case, e. g. `en-US` or `zh-HANS`.
+fn language_tag(language: &Language) -> String {
+    match language.as_str().split_once('-') {
+        Some((language, region)) => format!(
+            "{}-{}",
+            language.to_ascii_lowercase(),
+            region.to_ascii_uppercase()
+        ),
+        None => language.as_str().to_ascii_lowercase(),
+    }
+}
+
+/// Escape text for XML content and attributes. Control characters are not allowed in XML 1.0
+/// and are dropped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tmx_export() {
+        let mut memory = TranslationMemory::new();
+        memory.add(
+            Language::EnUs,
+            &["<b>Grüße</b> & \"mehr\"\u{1}"],
+            &[TranslatedText {
+                detected_source_language: "DE".to_string(),
+                text: "<b>Greetings</b> & \"more\"".to_string(),
+                billed_characters: None,
+                model_type_used: None,
+            }],
+        );
+        assert_eq!(
+            memory.to_tmx(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE tmx SYSTEM "tmx14.dtd">
+<tmx version="1.4">
+  <header creationtool="deepl-api" creationtoolversion="0.2.0" segtype="block" o-tmf="DeepL" adminlang="en" srclang="de" datatype="plaintext"/>
+  <body>
+    <tu>
+      <tuv xml:lang="de"><seg>&lt;b&gt;Grüße&lt;/b&gt; &amp; &quot;mehr&quot;</seg></tuv>
+      <tuv xml:lang="en-US"><seg>&lt;b&gt;Greetings&lt;/b&gt; &amp; &quot;more&quot;</seg></tuv>
+    </tu>
+  </body>
+</tmx>
+"#
+        );
+
+        memory.add_segment(Segment {
+            source_language: Language::Fr,
+            target_language: Language::ZhHans,
+            source: "oui".to_string(),
+            target: "是".to_string(),
+        });
+        let tmx = memory.to_tmx();
+        assert!(tmx.contains(r#"srclang="*all*""#));
+        assert!(tmx.contains(r#"<tuv xml:lang="zh-HANS"><seg>是</seg></tuv>"#));
+        assert_eq!(memory.segments().len(), 2);
+    }
+}
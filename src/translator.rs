@@ -0,0 +1,126 @@
+//! Abstraction over translation providers, see [Translator].
+
+use crate::{
+    DeepL, LanguageList, Result, TranslatableTextList, TranslatedText, TranslationOptions,
+    UsageInformation,
+};
+
+/// The core operations of a translation service, implemented by [DeepL].
+///
+/// Write code that depends on translation against this trait instead of [DeepL] directly, to swap
+/// in fakes for unit tests, caching wrappers or alternative providers. The trait is object safe,
+/// so `&dyn Translator` and `Box<dyn Translator>` work as well.
+///
+/// Like the rest of this crate, the trait is blocking; run it on a worker thread when calling it
+/// from async code.
+///
+/// # Example
+///
+/// ```rust
+/// use deepl_api::*;
+///
+/// struct Shouting;
+///
+/// impl Translator for Shouting {
+///     fn usage_information(&self) -> Result<UsageInformation> {
+///         Ok(UsageInformation::default())
+///     }
+///
+///     fn source_languages(&self) -> Result<LanguageList> {
+///         Ok(Vec::new())
+///     }
+///
+///     fn target_languages(&self) -> Result<LanguageList> {
+///         Ok(Vec::new())
+///     }
+///
+///     fn translate(
+///         &self,
+///         _options: Option<TranslationOptions>,
+///         text_list: TranslatableTextList,
+///     ) -> Result<Vec<TranslatedText>> {
+///         Ok(text_list
+///             .texts
+///             .iter()
+///             .map(|text| TranslatedText {
+///                 detected_source_language: "EN".to_string(),
+///                 text: text.to_uppercase(),
+///                 billed_characters: None,
+///                 model_type_used: None,
+///             })
+///             .collect())
+///     }
+/// }
+///
+/// fn greet(translator: &dyn Translator) -> String {
+///     let texts = TranslatableTextList::new(Language::De, vec!["hello"]);
+///     translator.translate(None, texts).unwrap().remove(0).text
+/// }
+///
+/// assert_eq!(greet(&Shouting), "HELLO");
+/// ```
+pub trait Translator {
+    /// Retrieve information about API usage & limits, see [DeepL::usage_information].
+    fn usage_information(&self) -> Result<UsageInformation>;
+
+    /// Retrieve all currently available source languages, see [DeepL::source_languages].
+    fn source_languages(&self) -> Result<LanguageList>;
+
+    /// Retrieve all currently available target languages, see [DeepL::target_languages].
+    fn target_languages(&self) -> Result<LanguageList>;
+
+    /// Translate one or more text chunks at once, see [DeepL::translate]. Implementations must
+    /// return exactly one translation per text, in the order of the texts.
+    fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>>;
+}
+
+impl Translator for DeepL {
+    fn usage_information(&self) -> Result<UsageInformation> {
+        DeepL::usage_information(self)
+    }
+
+    fn source_languages(&self) -> Result<LanguageList> {
+        DeepL::source_languages(self)
+    }
+
+    fn target_languages(&self) -> Result<LanguageList> {
+        DeepL::target_languages(self)
+    }
+
+    fn translate(
+        &self,
+        options: Option<TranslationOptions>,
+        text_list: TranslatableTextList,
+    ) -> Result<Vec<TranslatedText>> {
+        DeepL::translate(self, options, text_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn deepl_translator() {
+        let server = MockServer::start(|request| match request.path.as_str() {
+            "/v2/usage" => {
+                MockResponse::json(200, r#"{"character_count": 5, "character_limit": 10}"#)
+            }
+            _ => MockResponse::json(
+                200,
+                r#"{"translations": [{"detected_source_language": "EN", "text": "Hallo"}]}"#,
+            ),
+        });
+        let translator: Box<dyn Translator> =
+            Box::new(DeepL::new("key".to_string(), false).with_base_url(server.url()));
+        assert_eq!(translator.usage_information().unwrap().character_count, 5);
+        let texts = TranslatableTextList::new("DE", vec!["Hello"]);
+        assert_eq!(translator.translate(None, texts).unwrap()[0].text, "Hallo");
+        assert_eq!(server.requests().len(), 2);
+    }
+}
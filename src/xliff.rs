@@ -0,0 +1,618 @@
+//! Translation of [XLIFF](https://docs.oasis-open.org/xliff/) 1.2 and 2.0 documents, the exchange
+//! format of CAT tools and many localization platforms.
+//!
+//! [XliffDocument] finds the segments of a document: the `<trans-unit>` elements of XLIFF 1.2 and
+//! the `<segment>` elements of XLIFF 2.0, skipping units with `translate="no"`.
+//! [DeepL::translate_xliff] translates the segments without a target and writes `<target>` elements
+//! with the state `translated`, sending one request per language pair, as the `<file>` elements of
+//! XLIFF 1.2 may declare different languages. The source is translated as XML, so inline tags like `<g>`, `<x/>`,
+//! `<ph>` or `<pc>` are kept, and the content of the native code tags `<ph>`, `<bpt>`, `<ept>` and
+//! `<it>` is not translated. The rest of the document is written back as it was.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use deepl_api::xliff::XliffDocument;
+//! use deepl_api::*;
+//!
+//! let deepl = DeepL::new(std::env::var("DEEPL_API_KEY").unwrap(), false);
+//! let mut document = XliffDocument::parse(
+//!     r#"<xliff version="1.2"><file source-language="en" target-language="de"><body>
+//!     <trans-unit id="1"><source>Hello <g id="1">World</g></source></trans-unit>
+//!     </body></file></xliff>"#,
+//! )
+//! .unwrap();
+//! deepl.translate_xliff(&mut document, None).unwrap();
+//! assert!(document
+//!     .to_xml()
+//!     .contains(r#"<target state="translated">Hallo <g id="1">Welt</g></target>"#));
+//! ```
+
+use crate::{DeepL, Error, Result, TagHandling, TranslatableTextList, TranslationOptions};
+
+/// Inline tags of XLIFF whose content is native code, not text.
+const NATIVE_CODE_TAGS: [&str; 4] = ["ph", "bpt", "ept", "it"];
+
+/// The XLIFF version of a document.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum XliffVersion {
+    /// XLIFF 1.2.
+    V1_2,
+    /// XLIFF 2.0 or 2.1.
+    V2,
+}
+
+/// A segment of an [XliffDocument].
+#[derive(Clone, Debug, PartialEq)]
+pub struct XliffSegment {
+    /// The id of the segment, or of its unit if the segment has none.
+    pub id: String,
+    /// The content of the `<source>` element, with inline tags.
+    pub source: String,
+    /// The content of the `<target>` element, if any.
+    pub target: Option<String>,
+    /// The `state` of the target (XLIFF 1.2) or of the segment (XLIFF 2.0).
+    pub state: Option<String>,
+    // Byte positions in the document, and the line break and indentation of an inserted target.
+    source_end: usize,
+    indent: String,
+    target_range: Option<(usize, usize)>,
+    // The start tag that holds the state: `<target>` (1.2) or `<segment>` (2.0).
+    state_tag: Option<(usize, usize)>,
+    translated: bool,
+    // The languages of the `<file>` (1.2) or the document (2.0).
+    source_language: Option<String>,
+    target_language: Option<String>,
+}
+
+impl XliffSegment {
+    /// Whether the segment needs a translation: it has no or an empty target, or, with
+    /// XLIFF 1.2, a target in the state `new` or `needs-translation`.
+    pub fn is_untranslated(&self) -> bool {
+        self.target
+            .as_ref()
+            .is_none_or(|target| target.trim().is_empty())
+            || matches!(
+                self.state.as_deref(),
+                Some("new") | Some("needs-translation")
+            )
+    }
+}
+
+/// An XLIFF document whose segments can be translated, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct XliffDocument {
+    xml: String,
+    version: XliffVersion,
+    source_language: Option<String>,
+    target_language: Option<String>,
+    segments: Vec<XliffSegment>,
+}
+
+impl XliffDocument {
+    /// Parse an XLIFF 1.2 or 2.0 document. Fails with [InvalidXliff](Error::InvalidXliff) if the
+    /// document has no `<xliff>` element with a supported version, or a segment is malformed.
+    pub fn parse(xml: &str) -> Result<XliffDocument> {
+        let root = element(xml, 0, xml.len(), "xliff")
+            .ok_or_else(|| invalid("missing <xliff> element"))?;
+        let root_tag = &xml[root.start..root.content_start];
+        let version = match attribute(root_tag, "version").as_deref() {
+            Some("1.2") => XliffVersion::V1_2,
+            Some(version) if version.starts_with("2.") => XliffVersion::V2,
+            Some(version) => return Err(invalid(&format!("unsupported version {}", version))),
+            None => return Err(invalid("missing version")),
+        };
+        let mut document = XliffDocument {
+            xml: xml.to_string(),
+            version,
+            source_language: None,
+            target_language: None,
+            segments: Vec::new(),
+        };
+        match version {
+            XliffVersion::V1_2 => {
+                let files = elements(xml, root.content_start, root.content_end, "file");
+                if let Some(file) = files.first() {
+                    let file_tag = &xml[file.start..file.content_start];
+                    document.source_language = attribute(file_tag, "source-language");
+                    document.target_language = attribute(file_tag, "target-language");
+                }
+                for unit in elements(xml, root.content_start, root.content_end, "trans-unit") {
+                    let unit_tag = &xml[unit.start..unit.content_start];
+                    if attribute(unit_tag, "translate").as_deref() == Some("no") {
+                        continue;
+                    }
+                    // Sources and targets of <alt-trans> elements are alternatives, not the segment.
+                    let end = element(xml, unit.content_start, unit.content_end, "alt-trans")
+                        .map_or(unit.content_end, |alt| alt.start);
+                    let id = attribute(unit_tag, "id").unwrap_or_default();
+                    let mut segment = segment(xml, unit.content_start, end, id, None)?;
+                    let file = files
+                        .iter()
+                        .find(|file| file.start < unit.start && unit.end <= file.end);
+                    if let Some(file) = file {
+                        let file_tag = &xml[file.start..file.content_start];
+                        segment.source_language = attribute(file_tag, "source-language");
+                        segment.target_language = attribute(file_tag, "target-language");
+                    }
+                    document.segments.push(segment);
+                }
+            }
+            XliffVersion::V2 => {
+                document.source_language = attribute(root_tag, "srcLang");
+                document.target_language = attribute(root_tag, "trgLang");
+                for unit in elements(xml, root.content_start, root.content_end, "unit") {
+                    let unit_tag = &xml[unit.start..unit.content_start];
+                    if attribute(unit_tag, "translate").as_deref() == Some("no") {
+                        continue;
+                    }
+                    let unit_id = attribute(unit_tag, "id").unwrap_or_default();
+                    for part in elements(xml, unit.content_start, unit.content_end, "segment") {
+                        let segment_tag = &xml[part.start..part.content_start];
+                        let id = attribute(segment_tag, "id").unwrap_or_else(|| unit_id.clone());
+                        let state_tag = Some((part.start, part.content_start));
+                        let mut segment =
+                            segment(xml, part.content_start, part.content_end, id, state_tag)?;
+                        segment.source_language = document.source_language.clone();
+                        segment.target_language = document.target_language.clone();
+                        document.segments.push(segment);
+                    }
+                }
+            }
+        }
+        Ok(document)
+    }
+
+    /// The XLIFF version of the document.
+    pub fn version(&self) -> XliffVersion {
+        self.version
+    }
+
+    /// The source language of the document, if declared. With XLIFF 1.2, this is the source
+    /// language of the first `<file>`.
+    pub fn source_language(&self) -> Option<&str> {
+        self.source_language.as_deref()
+    }
+
+    /// The target language of the document, if declared. With XLIFF 1.2, this is the target
+    /// language of the first `<file>`.
+    pub fn target_language(&self) -> Option<&str> {
+        self.target_language.as_deref()
+    }
+
+    /// The translatable segments, in document order.
+    pub fn segments(&self) -> &[XliffSegment] {
+        &self.segments
+    }
+
+    /// Set the target of the segment at `index` (in [segments](XliffDocument::segments)) to
+    /// `target`, which may contain inline tags, and its state to `translated`.
+    pub fn set_target(&mut self, index: usize, target: impl Into<String>) {
+        let segment = &mut self.segments[index];
+        segment.target = Some(target.into());
+        segment.state = Some("translated".to_string());
+        segment.translated = true;
+    }
+
+    /// Serialize the document with the targets that were [set](XliffDocument::set_target).
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::with_capacity(self.xml.len() * 2);
+        let mut copied = 0;
+        for segment in self.segments.iter().filter(|segment| segment.translated) {
+            let target = segment.target.as_deref().unwrap_or_default();
+            if self.version == XliffVersion::V2 {
+                if let Some((start, end)) = segment.state_tag {
+                    xml.push_str(&self.xml[copied..start]);
+                    xml.push_str(&set_attribute(&self.xml[start..end], "state", "translated"));
+                    copied = end;
+                }
+            }
+            match segment.target_range {
+                Some((start, end)) => {
+                    let tag_end = start + self.xml[start..end].find('>').unwrap_or(0) + 1;
+                    // Empty targets like `<target/>` get an end tag.
+                    let start_tag = self.xml[start..tag_end].trim_end_matches('>');
+                    let mut start_tag = format!("{}>", start_tag.trim_end_matches('/'));
+                    if self.version == XliffVersion::V1_2 {
+                        start_tag = set_attribute(&start_tag, "state", "translated");
+                    }
+                    xml.push_str(&self.xml[copied..start]);
+                    xml.push_str(&format!("{}{}</target>", start_tag, target));
+                    copied = end;
+                }
+                None => {
+                    let state = match self.version {
+                        XliffVersion::V1_2 => " state=\"translated\"",
+                        XliffVersion::V2 => "",
+                    };
+                    xml.push_str(&self.xml[copied..segment.source_end]);
+                    xml.push_str(&format!(
+                        "{}<target{}>{}</target>",
+                        segment.indent, state, target
+                    ));
+                    copied = segment.source_end;
+                }
+            }
+        }
+        xml.push_str(&self.xml[copied..]);
+        xml
+    }
+}
+
+impl DeepL {
+    /// Translate the [untranslated](XliffSegment::is_untranslated) segments of `document` into the
+    /// target language of their `<file>`, see the [module documentation](crate::xliff). Returns the
+    /// number of translated segments.
+    ///
+    /// The segments of each language pair are sent in as few requests as possible, with
+    /// [tag_handling](TranslationOptions::tag_handling) set to [Xml](TagHandling::Xml). Regional
+    /// source languages like `en-US` are sent as their base language, as DeepL expects. Fails
+    /// with [InvalidXliff](Error::InvalidXliff) if a segment to translate has no target language.
+    pub fn translate_xliff(
+        &self,
+        document: &mut XliffDocument,
+        options: Option<TranslationOptions>,
+    ) -> Result<usize> {
+        // Untranslated segments by source and target language, in document order.
+        let mut pairs: Vec<(_, Vec<usize>)> = Vec::new();
+        for (index, segment) in document.segments.iter().enumerate() {
+            if !segment.is_untranslated() || segment.source.trim().is_empty() {
+                continue;
+            }
+            let target_language = segment.target_language.clone().ok_or_else(|| {
+                invalid(&format!("segment {} has no target language", segment.id))
+            })?;
+            let source_language = segment.source_language.as_deref().map(base_language);
+            let pair = (source_language, target_language);
+            match pairs.iter_mut().find(|(other, _)| *other == pair) {
+                Some((_, indices)) => indices.push(index),
+                None => pairs.push((pair, vec![index])),
+            }
+        }
+        let mut options = options.unwrap_or_default().tag_handling(TagHandling::Xml);
+        if options.ignore_tags.is_none() {
+            options = options.ignore_tags(NATIVE_CODE_TAGS);
+        }
+        let mut translated = 0;
+        for ((source_language, target_language), indices) in pairs {
+            let texts = indices
+                .iter()
+                .map(|&index| document.segments[index].source.clone());
+            let mut text_list = TranslatableTextList::new(target_language.as_str(), texts);
+            if let Some(source_language) = &source_language {
+                text_list = text_list.with_source_language(source_language.as_str());
+            }
+            let translations = self.translate(Some(options.clone()), text_list)?;
+            for (index, translation) in indices.iter().zip(translations) {
+                document.set_target(*index, translation.text);
+            }
+            translated += indices.len();
+        }
+        Ok(translated)
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidXliff(reason.to_string())
+}
+
+/// The base language of a language tag like `en-US` or `pt_BR`, in upper case.
+fn base_language(language: &str) -> String {
+    language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_uppercase()
+}
+
+/// The byte ranges of an element: the start tag from `start` to `content_start`, and the end tag
+/// from `content_end` to `end`. Empty elements have no content and no end tag.
+#[derive(Clone, Copy, Debug)]
+struct Element {
+    start: usize,
+    content_start: usize,
+    content_end: usize,
+    end: usize,
+}
+
+/// Private helper that finds the first element `name` within `xml[from..to]`.
+fn element(xml: &str, from: usize, to: usize, name: &str) -> Option<Element> {
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut position = from;
+    loop {
+        let start = position + xml[position..to].find(&open)?;
+        position = start + open.len();
+        // Skip elements whose name only starts with `name`, like `<target-language`.
+        match xml[position..to].chars().next() {
+            Some(c) if c.is_whitespace() || c == '>' || c == '/' => {}
+            _ => continue,
+        }
+        let content_start = position + xml[position..to].find('>')? + 1;
+        if xml[..content_start].ends_with("/>") {
+            return Some(Element {
+                start,
+                content_start,
+                content_end: content_start,
+                end: content_start,
+            });
+        }
+        let content_end = content_start + xml[content_start..to].find(&close)?;
+        return Some(Element {
+            start,
+            content_start,
+            content_end,
+            end: content_end + close.len(),
+        });
+    }
+}
+
+/// All elements `name` within `xml[from..to]`, not nested in each other.
+fn elements(xml: &str, from: usize, to: usize, name: &str) -> Vec<Element> {
+    let mut elements = Vec::new();
+    let mut position = from;
+    while let Some(element) = element(xml, position, to, name) {
+        position = element.end;
+        elements.push(element);
+    }
+    elements
+}
+
+/// Private helper that reads the segment with the `<source>` and `<target>` in `xml[from..to]`.
+fn segment(
+    xml: &str,
+    from: usize,
+    to: usize,
+    id: String,
+    state_tag: Option<(usize, usize)>,
+) -> Result<XliffSegment> {
+    let source = element(xml, from, to, "source")
+        .ok_or_else(|| invalid(&format!("segment {} has no <source>", id)))?;
+    let target = element(xml, from, to, "target");
+    let line_start = xml[..source.start]
+        .rfind('\n')
+        .map_or(source.start, |n| n + 1);
+    let indent = &xml[line_start..source.start];
+    let indent = match indent.trim().is_empty() && line_start < source.start {
+        true => format!("\n{}", indent),
+        false => String::new(),
+    };
+    let state_tag = match state_tag {
+        Some(state_tag) => Some(state_tag),
+        None => target.map(|target| (target.start, target.content_start)),
+    };
+    Ok(XliffSegment {
+        state: state_tag.and_then(|(start, end)| attribute(&xml[start..end], "state")),
+        id,
+        source: xml[source.content_start..source.content_end].to_string(),
+        target: target.map(|target| xml[target.content_start..target.content_end].to_string()),
+        source_end: source.end,
+        indent,
+        target_range: target.map(|target| (target.start, target.end)),
+        state_tag,
+        translated: false,
+        source_language: None,
+        target_language: None,
+    })
+}
+
+/// The value of the attribute `name` of the start tag `tag`, without unescaping.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    attribute_range(tag, name).map(|(start, end)| tag[start..end].to_string())
+}
+
+/// The byte range of the value of the attribute `name` in the start tag `tag`.
+fn attribute_range(tag: &str, name: &str) -> Option<(usize, usize)> {
+    let mut position = 0;
+    while let Some(found) = tag[position..].find(name) {
+        let start = position + found;
+        position = start + name.len();
+        let preceded = tag[..start].ends_with(char::is_whitespace);
+        let rest = tag[position..].trim_start();
+        if let (true, Some(rest)) = (preceded, rest.strip_prefix('=')) {
+            let rest = rest.trim_start();
+            let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+            let value_start = tag.len() - rest.len() + 1;
+            let value_end = value_start + tag[value_start..].find(quote)?;
+            return Some((value_start, value_end));
+        }
+    }
+    None
+}
+
+/// Private helper that sets the attribute `name` of the start tag `tag` to `value`.
+fn set_attribute(tag: &str, name: &str, value: &str) -> String {
+    match attribute_range(tag, name) {
+        Some((start, end)) => format!("{}{}{}", &tag[..start], value, &tag[end..]),
+        None => {
+            let end = tag.len() - if tag.ends_with("/>") { 2 } else { 1 };
+            format!("{} {}=\"{}\"{}", &tag[..end], name, value, &tag[end..])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    const XLIFF_1_2: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">
+  <file source-language="en" target-language="de" datatype="plaintext" original="app">
+    <body>
+      <trans-unit id="greeting">
+        <source>Hello <g id="1">World</g></source>
+      </trans-unit>
+      <trans-unit id="done">
+        <source>Done</source>
+        <target state="final">Fertig</target>
+      </trans-unit>
+      <trans-unit id="code" translate="no">
+        <source>main()</source>
+      </trans-unit>
+      <trans-unit id="new">
+        <source>Save <ph id="1">%s</ph></source>
+        <target xml:lang="de" state="needs-translation"/>
+        <alt-trans><target>Sichern</target></alt-trans>
+      </trans-unit>
+    </body>
+  </file>
+</xliff>
+"#;
+
+    const XLIFF_2_0: &str = r#"<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="en" trgLang="fr">
+  <file id="f1">
+    <unit id="u1">
+      <segment id="s1"><source>Hello</source></segment>
+      <segment id="s2" state="final"><source>World</source><target>Monde</target></segment>
+    </unit>
+    <unit id="u2"><segment><source>Open <pc id="1">file</pc></source><target></target></segment></unit>
+  </file>
+</xliff>"#;
+
+    #[test]
+    fn parse_xliff() {
+        let mut document = XliffDocument::parse(XLIFF_1_2).unwrap();
+        assert_eq!(document.version(), XliffVersion::V1_2);
+        assert_eq!(document.source_language(), Some("en"));
+        assert_eq!(document.target_language(), Some("de"));
+        let segments = document.segments();
+        assert_eq!(
+            segments.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(),
+            ["greeting", "done", "new"]
+        );
+        assert_eq!(segments[0].source, r#"Hello <g id="1">World</g>"#);
+        assert_eq!(segments[1].target.as_deref(), Some("Fertig"));
+        assert_eq!(segments[2].state.as_deref(), Some("needs-translation"));
+        assert_eq!(
+            segments
+                .iter()
+                .map(|s| s.is_untranslated())
+                .collect::<Vec<_>>(),
+            [true, false, true]
+        );
+
+        // Unchanged documents are written back as they were.
+        assert_eq!(document.to_xml(), XLIFF_1_2);
+        document.set_target(0, r#"Hallo <g id="1">Welt</g>"#);
+        document.set_target(2, r#"Speichern <ph id="1">%s</ph>"#);
+        assert_eq!(
+            document.to_xml(),
+            XLIFF_1_2
+                .replace(
+                    "World</g></source>\n",
+                    "World</g></source>\n        <target state=\"translated\">Hallo <g id=\"1\">Welt</g></target>\n"
+                )
+                .replace(
+                    r#"<target xml:lang="de" state="needs-translation"/>"#,
+                    r#"<target xml:lang="de" state="translated">Speichern <ph id="1">%s</ph></target>"#
+                )
+        );
+
+        let mut document = XliffDocument::parse(XLIFF_2_0).unwrap();
+        assert_eq!(document.version(), XliffVersion::V2);
+        assert_eq!(document.target_language(), Some("fr"));
+        let segments = document.segments();
+        assert_eq!(
+            segments.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(),
+            ["s1", "s2", "u2"]
+        );
+        assert_eq!(segments[1].state.as_deref(), Some("final"));
+        document.set_target(0, "Bonjour");
+        document.set_target(2, r#"Ouvrir <pc id="1">fichier</pc>"#);
+        assert_eq!(
+            document.to_xml(),
+            XLIFF_2_0
+                .replace(
+                    r#"<segment id="s1"><source>Hello</source>"#,
+                    r#"<segment id="s1" state="translated"><source>Hello</source><target>Bonjour</target>"#
+                )
+                .replace(
+                    r#"<segment><source>Open <pc id="1">file</pc></source><target></target>"#,
+                    r#"<segment state="translated"><source>Open <pc id="1">file</pc></source><target>Ouvrir <pc id="1">fichier</pc></target>"#
+                )
+        );
+
+        for invalid in [
+            "<html/>",
+            r#"<xliff version="3.0"/>"#,
+            r#"<xliff version="1.2"><trans-unit id="a"></trans-unit></xliff>"#,
+        ] {
+            assert!(matches!(
+                XliffDocument::parse(invalid),
+                Err(Error::InvalidXliff(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn translate_xliff() {
+        let server = MockServer::translating(|text| {
+            text.replace("Hello", "Hallo").replace("Save", "Speichern")
+        });
+        let deepl = server.client();
+        let mut document = XliffDocument::parse(XLIFF_1_2).unwrap();
+        assert_eq!(deepl.translate_xliff(&mut document, None).unwrap(), 2);
+        let request = &server.requests()[0];
+        assert_eq!(request.param_values("source_lang"), ["EN"]);
+        assert_eq!(request.param_values("target_lang"), ["DE"]);
+        assert_eq!(request.param_values("ignore_tags"), ["ph,bpt,ept,it"]);
+        assert_eq!(
+            document.segments()[2].target.as_deref(),
+            Some(r#"Speichern <ph id="1">%s</ph>"#)
+        );
+        assert!(!document.segments().iter().any(|s| s.is_untranslated()));
+
+        // Translated documents need no further requests.
+        let mut document = XliffDocument::parse(&document.to_xml()).unwrap();
+        assert_eq!(deepl.translate_xliff(&mut document, None).unwrap(), 0);
+        assert_eq!(server.requests().len(), 1);
+
+        let mut document = XliffDocument::parse(
+            r#"<xliff version="2.0"><unit><segment><source>a</source></segment></unit></xliff>"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            deepl.translate_xliff(&mut document, None),
+            Err(Error::InvalidXliff(_))
+        ));
+    }
+
+    #[test]
+    fn translate_xliff_files() {
+        let server = MockServer::translating(|text| format!("[{}]", text));
+        let deepl = server.client();
+        let mut document = XliffDocument::parse(
+            r#"<xliff version="1.2">
+  <file source-language="en-US" target-language="de"><body>
+    <trans-unit id="a"><source>One</source></trans-unit>
+  </body></file>
+  <file source-language="en-US" target-language="fr"><body>
+    <trans-unit id="b"><source>Two</source></trans-unit>
+  </body></file>
+  <file source-language="en_GB" target-language="de"><body>
+    <trans-unit id="c"><source>Three</source></trans-unit>
+  </body></file>
+</xliff>"#,
+        )
+        .unwrap();
+        assert_eq!(document.target_language(), Some("de"));
+        assert_eq!(deepl.translate_xliff(&mut document, None).unwrap(), 3);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].param_values("source_lang"), ["EN"]);
+        assert_eq!(requests[0].param_values("target_lang"), ["DE"]);
+        assert_eq!(requests[0].param_values("text"), ["One", "Three"]);
+        assert_eq!(requests[1].param_values("target_lang"), ["FR"]);
+        assert_eq!(requests[1].param_values("text"), ["Two"]);
+        assert_eq!(
+            document
+                .segments()
+                .iter()
+                .map(|s| s.target.as_deref().unwrap())
+                .collect::<Vec<_>>(),
+            ["[One]", "[Two]", "[Three]"]
+        );
+        assert_eq!(base_language("pt-BR"), "PT");
+    }
+}
@@ -0,0 +1,68 @@
+//! Private helpers for escaping text sent with XML [tag handling](crate::TagHandling::Xml).
+
+/// The predefined entities of XML.
+const ENTITIES: [(&str, char); 5] = [
+    ("&lt;", '<'),
+    ("&gt;", '>'),
+    ("&amp;", '&'),
+    ("&quot;", '"'),
+    ("&apos;", '\''),
+];
+
+/// Append `text` to `xml`, escaping the characters that would be taken for markup.
+pub(crate) fn escape_into(text: &str, xml: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            c => xml.push(c),
+        }
+    }
+}
+
+/// The predefined entity at the start of `xml`, if any, as character and length.
+pub(crate) fn entity(xml: &str) -> Option<(char, usize)> {
+    ENTITIES
+        .iter()
+        .find(|(entity, _)| xml.starts_with(entity))
+        .map(|(entity, c)| (*c, entity.len()))
+}
+
+/// Replace the predefined entities of `xml` by their characters.
+pub(crate) fn unescape(xml: &str) -> String {
+    let mut text = String::with_capacity(xml.len());
+    let mut i = 0;
+    while let Some(c) = xml[i..].chars().next() {
+        match entity(&xml[i..]) {
+            Some((c, length)) => {
+                text.push(c);
+                i += length;
+            }
+            None => {
+                text.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_and_unescape() {
+        let mut xml = String::new();
+        escape_into("a < b && \"c\" > 'd'", &mut xml);
+        assert_eq!(xml, "a &lt; b &amp;&amp; \"c\" &gt; 'd'");
+        assert_eq!(unescape(&xml), "a < b && \"c\" > 'd'");
+        assert_eq!(
+            unescape("&amp;lt; &quot;&apos; &nbsp; ä&"),
+            "&lt; \"' &nbsp; ä&"
+        );
+        assert_eq!(entity("&gt;x"), Some(('>', 4)));
+        assert_eq!(entity("x&gt;"), None);
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "derive")]
+
+use deepl_api::Translate;
+
+#[derive(Translate)]
+struct Product {
+    #[allow(dead_code)]
+    sku: String,
+    #[translate]
+    title: String,
+    #[translate]
+    description: Option<String>,
+    #[translate]
+    tags: Vec<String>,
+}
+
+#[derive(Translate)]
+struct Caption(#[translate] String, u32);
+
+#[test]
+fn test_derive_translate() {
+    let mut product = Product {
+        sku: "C-1".to_string(),
+        title: "Chair".to_string(),
+        description: Some("Made of wood".to_string()),
+        tags: vec!["wood".to_string(), "furniture".to_string()],
+    };
+    let fields: Vec<String> = product
+        .translatable_fields()
+        .into_iter()
+        .map(|field| field.clone())
+        .collect();
+    assert_eq!(fields, vec!["Chair", "Made of wood", "wood", "furniture"]);
+
+    let mut caption = Caption("Hello".to_string(), 1);
+    for field in caption.translatable_fields() {
+        field.push('!');
+    }
+    assert_eq!(caption.0, "Hello!");
+    assert_eq!(caption.1, 1);
+}